@@ -0,0 +1,400 @@
+//! Benchmarks solvers on generated inputs of increasing size, then fits `time ~ size^k` to the measurements and prints
+//! the growth exponent `k` of each case, flagging those that grow noticeably faster than expected.
+//!
+//! The generated inputs only need to be shaped like the real ones, not to have meaningful answers, and they stay within
+//! the limits the solvers assume of the real inputs.
+//!
+//! Only the days with a `solve_input` can be handed generated inputs; the others include theirs at compile time, so
+//! they're left out.
+use std::{
+    cell::RefCell,
+    fmt::Write,
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rand::prelude::*;
+
+/// How much faster than expected a case may grow before it is flagged.
+const TOLERANCE: f64 = 0.25;
+
+struct Case {
+    name: &'static str,
+    /// What the sizes count, for the report.
+    unit: &'static str,
+    sizes: &'static [usize],
+    generate: fn(&mut StdRng, usize) -> String,
+    solve: fn(&str),
+    expected: f64,
+}
+
+const CASES: &[Case] = &[
+    Case {
+        name: "day01",
+        unit: "lines",
+        sizes: &[1_000, 2_000, 4_000, 8_000, 16_000, 32_000, 64_000],
+        generate: |rng, n| {
+            let mut input = String::new();
+            for _ in 0..n {
+                writeln!(
+                    input,
+                    "{}   {}",
+                    rng.gen_range(10_000..100_000),
+                    rng.gen_range(10_000..100_000)
+                )
+                .unwrap();
+            }
+            input
+        },
+        solve: |input| drop(black_box(day01::solve_input(input))),
+        expected: 1.0,
+    },
+    Case {
+        name: "day02",
+        unit: "reports",
+        sizes: &[1_000, 4_000, 16_000, 64_000, 256_000],
+        generate: |rng, n| {
+            let mut input = String::new();
+            for _ in 0..n {
+                let mut level = rng.gen_range(30..70i32);
+                let direction = if rng.gen() { 1 } else { -1 };
+                for i in 0..rng.gen_range(5..=8) {
+                    if i != 0 {
+                        input.push(' ');
+                        level += direction * rng.gen_range(-1..=4);
+                    }
+                    write!(input, "{level}").unwrap();
+                }
+                input.push('\n');
+            }
+            input
+        },
+        solve: |input| drop(black_box(day02::solve_input(input))),
+        expected: 1.0,
+    },
+    Case {
+        name: "day03",
+        unit: "instructions",
+        sizes: &[1_000, 2_000, 4_000, 8_000, 16_000, 32_000, 64_000],
+        generate: |rng, n| {
+            let mut input = String::new();
+            for _ in 0..n {
+                match rng.gen_range(0..6) {
+                    0 => input.push_str("do()"),
+                    1 => input.push_str("don't()"),
+                    2 => input.push_str(
+                        ["mul(4*", "mul(6,9!", "?(12,34)", "mul ( 2 , 4 )", "don't", "}\n"]
+                            .choose(rng)
+                            .unwrap(),
+                    ),
+                    _ => write!(input, "mul({},{})", rng.gen_range(1..1000), rng.gen_range(1..1000)).unwrap(),
+                }
+            }
+            input
+        },
+        solve: |input| drop(black_box(day03::solve_input(input))),
+        expected: 1.0,
+    },
+    Case {
+        name: "day05",
+        unit: "updates",
+        sizes: &[250, 500, 1_000, 2_000, 4_000, 8_000, 16_000],
+        generate: |rng, n| {
+            // Like the real inputs, 49 two-digit pages with a rule for every pair of them.
+            let mut pages = (10..100).collect::<Vec<u32>>();
+            pages.shuffle(rng);
+            pages.truncate(49);
+            let mut rules = Vec::new();
+            for (i, before) in pages.iter().enumerate() {
+                for after in &pages[i + 1..] {
+                    rules.push(format!("{before}|{after}\n"));
+                }
+            }
+            rules.shuffle(rng);
+
+            let mut input = rules.concat();
+            for _ in 0..n {
+                input.push('\n');
+                let len = rng.gen_range(2..=11) * 2 + 1;
+                let update = pages.choose_multiple(rng, len).map(u32::to_string).collect::<Vec<_>>();
+                input.push_str(&update.join(","));
+            }
+            input.push('\n');
+            input
+        },
+        solve: |input| drop(black_box(day05::solve_input(input))),
+        expected: 1.0,
+    },
+    Case {
+        // Sides of up to 252 cells, so that coordinates fit in a `u8` as with the real inputs.
+        name: "day06",
+        unit: "cells",
+        sizes: &[1_000, 2_000, 4_000, 8_000, 16_000, 32_000, 64_000],
+        generate: lab,
+        solve: |input| {
+            black_box(day06::solve_input(input));
+        },
+        // Every cell of the path is tried as an obstruction, and the guard may then walk a turn per ring of the spiral.
+        expected: 1.5,
+    },
+    Case {
+        name: "day08-cells",
+        unit: "cells",
+        sizes: &[2_500, 5_000, 10_000, 20_000, 40_000, 80_000, 160_000],
+        generate: |rng, n| {
+            let side = (n as f64).sqrt() as usize;
+            antennas(rng, side, 40, 4)
+        },
+        solve: |input| {
+            black_box(day08::solve_input(input));
+        },
+        expected: 1.0,
+    },
+    Case {
+        name: "day08-antennas",
+        unit: "antennas per frequency",
+        sizes: &[32, 64, 128, 256, 512],
+        generate: |rng, n| antennas(rng, 100, 4, n),
+        solve: |input| {
+            black_box(day08::solve_input(input));
+        },
+        // Every pair of antennas of a frequency is checked.
+        expected: 2.0,
+    },
+    Case {
+        // Up to 128000 files, more than an `i16` could number, to check that IDs of any size work.
+        name: "day09",
+        unit: "digits",
+        sizes: &[1_000, 2_000, 4_000, 8_000, 16_000, 32_000, 64_000, 128_000, 256_000],
+        generate: |rng, n| {
+            (0..n | 1)
+                .map(|i| {
+                    let digit = if i % 2 == 0 {
+                        rng.gen_range(1..=9)
+                    } else {
+                        rng.gen_range(0..=9)
+                    };
+                    char::from(b'0' + digit)
+                })
+                .collect()
+        },
+        solve: |input| drop(black_box(day09::solve_input(input))),
+        expected: 1.0,
+    },
+    Case {
+        name: "day10",
+        unit: "cells",
+        sizes: &[2_500, 5_000, 10_000, 20_000, 40_000, 80_000, 160_000],
+        generate: |rng, n| {
+            // Heights mostly go up by one to the right and down, with some noise so that not every path does.
+            let side = (n as f64).sqrt() as usize;
+            let mut input = String::new();
+            for y in 0..side {
+                for x in 0..side {
+                    let noise = if rng.gen_ratio(1, 5) { rng.gen_range(0..10) } else { 0 };
+                    input.push(char::from(b'0' + ((y + x + noise) % 10) as u8));
+                }
+                input.push('\n');
+            }
+            input
+        },
+        solve: |input| {
+            black_box(day10::solve_input(input));
+        },
+        expected: 1.0,
+    },
+    Case {
+        name: "day22",
+        unit: "buyers",
+        sizes: &[250, 500, 1_000, 2_000, 4_000, 8_000],
+        generate: |rng, n| (0..n).map(|_| format!("{}\n", rng.gen_range(1..1 << 24))).collect(),
+        solve: |input| drop(black_box(day22::solve_input(input))),
+        expected: 1.0,
+    },
+    Case {
+        name: "day23-nodes",
+        unit: "nodes",
+        sizes: &[500, 1_000, 2_000, 4_000, 8_000, 16_000],
+        generate: |rng, n| lan(rng, n, 14),
+        solve: |input| drop(black_box(day23::solve_input(input))),
+        expected: 1.0,
+    },
+    Case {
+        // Triangles are counted per node in a `u8`, which caps the degree at 22.
+        name: "day23-degree",
+        unit: "degree",
+        sizes: &[4, 6, 8, 12, 16, 22],
+        generate: |rng, degree| lan(rng, 520, degree),
+        solve: |input| drop(black_box(day23::solve_input(input))),
+        // Every pair of neighbours is checked for an edge, with adjacency lists short enough to scan in constant time.
+        expected: 2.0,
+    },
+];
+
+/// A square lab of about `cells` cells in which the guard spirals out from the middle, turned by a wall at the end of
+/// each leg, so that like in the real inputs it walks a good part of the map before leaving it; the cells off its path
+/// are walls one time in 20.
+fn lab(rng: &mut StdRng, cells: usize) -> String {
+    let side = (cells as f64).sqrt() as usize;
+    let mut map = vec![vec![b'.'; side]; side];
+    let mut on_path = vec![vec![false; side]; side];
+    let (mut y, mut x) = (side / 2, side / 2);
+    on_path[y][x] = true;
+
+    let in_map = |y: usize, x: usize, (dy, dx): (isize, isize)| {
+        let y = y.checked_add_signed(dy).filter(|&y| y < side)?;
+        let x = x.checked_add_signed(dx).filter(|&x| x < side)?;
+        Some((y, x))
+    };
+    let mut leg = 2;
+    'spiral: for &dir in [(-1, 0), (0, 1), (1, 0), (0, -1)].iter().cycle() {
+        for _ in 0..leg {
+            let Some(next) = in_map(y, x, dir) else {
+                break 'spiral;
+            };
+            (y, x) = next;
+            on_path[y][x] = true;
+        }
+        let Some((wall_y, wall_x)) = in_map(y, x, dir) else {
+            break;
+        };
+        map[wall_y][wall_x] = b'#';
+        if dir.1 != 0 {
+            leg += 2;
+        }
+    }
+
+    for (row, on_path) in map.iter_mut().zip(on_path) {
+        for (cell, on_path) in row.iter_mut().zip(on_path) {
+            if !on_path && rng.gen_ratio(1, 20) {
+                *cell = b'#';
+            }
+        }
+    }
+    map[side / 2][side / 2] = b'^';
+
+    map.into_iter()
+        .map(|row| String::from_utf8(row).unwrap() + "\n")
+        .collect()
+}
+
+/// A square map with `per_frequency` antennas of each of `frequencies` frequencies at random places.
+fn antennas(rng: &mut StdRng, side: usize, frequencies: usize, per_frequency: usize) -> String {
+    const FREQUENCIES: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    let mut map = vec![vec![b'.'; side]; side];
+    for &frequency in &FREQUENCIES[..frequencies] {
+        for _ in 0..per_frequency {
+            map[rng.gen_range(0..side)][rng.gen_range(0..side)] = frequency;
+        }
+    }
+    map.into_iter()
+        .map(|row| String::from_utf8(row).unwrap() + "\n")
+        .collect()
+}
+
+/// Name of the `i`th computer: the real ones are two lowercase letters, but generated networks may need more.
+fn computer(mut i: usize) -> String {
+    let mut name = Vec::new();
+    for _ in 0..3 {
+        name.push(b'a' + (i % 26) as u8);
+        i /= 26;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap()
+}
+
+/// A random network of `n` computers, each connected to `degree` others (rounded down to an even number).
+fn lan(rng: &mut StdRng, n: usize, degree: usize) -> String {
+    // Link computers that are close to each other on a ring, so that there are plenty of triangles, under shuffled names.
+    let mut names = (0..n).map(computer).collect::<Vec<_>>();
+    names.shuffle(rng);
+    let mut input = String::new();
+    for i in 0..n {
+        for d in 1..=degree / 2 {
+            writeln!(input, "{}-{}", names[i], names[(i + d) % n]).unwrap();
+        }
+    }
+    input
+}
+
+/// Least-squares slope of the log of the time against the log of the size, i.e. `k` in `time ~ size^k`.
+fn growth_exponent(points: &[(usize, Duration)]) -> f64 {
+    let points = points
+        .iter()
+        .map(|&(size, time)| ((size as f64).ln(), time.as_secs_f64().ln()))
+        .collect::<Vec<_>>();
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let covariance = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum::<f64>();
+    let variance = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum::<f64>();
+    covariance / variance
+}
+
+fn median(mut times: Vec<Duration>) -> Duration {
+    times.sort_unstable();
+    times[times.len() / 2]
+}
+
+pub fn scaling(c: &mut Criterion) {
+    let mut report = String::new();
+
+    for case in CASES {
+        let mut group = c.benchmark_group(format!("scaling/{}", case.name));
+        group.sample_size(20).measurement_time(Duration::from_secs(2));
+
+        let mut points = Vec::new();
+        for &size in case.sizes {
+            let input = (case.generate)(&mut StdRng::seed_from_u64(size as u64), size);
+
+            // Keep the per-iteration times criterion measures, as it doesn't hand back its estimates.
+            let times = RefCell::new(Vec::new());
+            group.throughput(Throughput::Elements(size as u64));
+            group.bench_with_input(BenchmarkId::from_parameter(size), input.as_str(), |b, input| {
+                b.iter_custom(|iters| {
+                    let start = Instant::now();
+                    for _ in 0..iters {
+                        (case.solve)(black_box(input));
+                    }
+                    let elapsed = start.elapsed();
+                    times.borrow_mut().push(elapsed.div_f64(iters as f64));
+                    elapsed
+                })
+            });
+
+            // Cases excluded by a filter on the command line aren't run.
+            let times = times.into_inner();
+            if !times.is_empty() {
+                points.push((size, median(times)));
+            }
+        }
+        group.finish();
+
+        if points.len() >= 2 {
+            let k = growth_exponent(&points);
+            let flag = if k > case.expected + TOLERANCE {
+                "  <- grows faster than expected"
+            } else {
+                ""
+            };
+            writeln!(
+                report,
+                "{:<14} k = {k:.2} over {}..{} {} (expected {:.1}){flag}",
+                case.name,
+                points[0].0,
+                points[points.len() - 1].0,
+                case.unit,
+                case.expected
+            )
+            .unwrap();
+        }
+    }
+
+    if !report.is_empty() {
+        println!("\nGrowth exponents, fitted to time ~ size^k:\n{report}");
+    }
+}
+
+criterion_group!(benches, scaling);
+criterion_main!(benches);
@@ -0,0 +1,66 @@
+use std::{
+    env,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Workspace crates the solvers share, which are hashed as part of every solver.
+const SHARED: &[&str] = &["parse", "trace"];
+
+/// Hash everything that makes up a day's solver, i.e. its manifest, build script and sources, minus its input.
+fn hash_solver(hasher: &mut blake3::Hasher, path: &Path) {
+    if path.is_dir() {
+        let mut entries = fs::read_dir(path)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect::<Vec<_>>();
+        entries.sort_unstable();
+        entries.iter().for_each(|entry| hash_solver(hasher, entry));
+    } else if path.extension().is_some_and(|ext| ext == "rs" || ext == "toml") {
+        hasher.update(path.file_name().unwrap().as_encoded_bytes());
+        hasher.update(&fs::read(path).unwrap());
+    }
+}
+
+fn main() {
+    println!("cargo:rerun-if-env-changed={}", aoc_inputs::KEY_VAR);
+
+    let mut days = fs::read_dir(aoc_inputs::workspace_root())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.is_dir() && path.file_name().unwrap().to_string_lossy().starts_with("day"))
+        .collect::<Vec<_>>();
+    days.sort_unstable();
+
+    let mut shared = blake3::Hasher::new();
+    for name in SHARED {
+        let path = aoc_inputs::workspace_root().join(name);
+        println!("cargo:rerun-if-changed={}", path.display());
+        hash_solver(&mut shared, &path);
+    }
+
+    let mut out = String::from("pub fn hashes(day: &str) -> Option<(&'static str, &'static str)> {\n    match day {\n");
+    for day in days {
+        println!("cargo:rerun-if-changed={}", day.display());
+
+        // Days whose input can't be read simply aren't cached.
+        let Ok(input) = aoc_inputs::read_input(&day) else {
+            continue;
+        };
+
+        let mut solver = shared.clone();
+        hash_solver(&mut solver, &day);
+        writeln!(
+            out,
+            "        {:?} => Some(({:?}, {:?})),",
+            day.file_name().unwrap().to_string_lossy(),
+            solver.finalize().to_hex().as_str(),
+            blake3::hash(&input).to_hex().as_str(),
+        )
+        .unwrap();
+    }
+    out.push_str("        _ => None,\n    }\n}\n");
+
+    fs::write(PathBuf::from(env::var_os("OUT_DIR").unwrap()).join("hashes.rs"), out).unwrap();
+}
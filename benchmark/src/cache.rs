@@ -0,0 +1,64 @@
+//! On-disk cache of answers, keyed by day, solver version and input hash.
+//!
+//! The solver version is a hash of the day crate's sources and the input hash is a hash of its decrypted input, both
+//! computed by the build script: editing a day or its input makes its old answers unreachable, and they get pruned the
+//! next time the day is solved.
+use std::{collections::BTreeMap, fs, io, path::PathBuf};
+
+include!(concat!(env!("OUT_DIR"), "/hashes.rs"));
+
+type Key = (String, String, String);
+
+pub struct Cache {
+    path: PathBuf,
+    entries: BTreeMap<Key, (String, String)>,
+}
+
+impl Cache {
+    pub fn path() -> PathBuf {
+        crate::target_dir().join("aoc-answers.tsv")
+    }
+
+    pub fn load() -> Self {
+        let path = Self::path();
+        let entries = fs::read_to_string(&path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split('\t').map(str::to_owned);
+                let key = (fields.next()?, fields.next()?, fields.next()?);
+                Some((key, (fields.next()?, fields.next()?)))
+            })
+            .collect();
+        Self { path, entries }
+    }
+
+    fn key(day: &str) -> Option<Key> {
+        let (solver, input) = hashes(day)?;
+        Some((day.to_owned(), solver.to_owned(), input.to_owned()))
+    }
+
+    pub fn get(&self, day: &str) -> Option<&(String, String)> {
+        self.entries.get(&Self::key(day)?)
+    }
+
+    pub fn insert(&mut self, day: &str, answers: (String, String)) {
+        let Some(key) = Self::key(day) else {
+            return;
+        };
+        self.entries.retain(|(d, _, _), _| d != day);
+        self.entries.insert(key, answers);
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let mut out = String::new();
+        for ((day, solver, input), (p1, p2)) in &self.entries {
+            out.push_str(&[day, solver, input, p1, p2].map(String::as_str).join("\t"));
+            out.push('\n');
+        }
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, out)
+    }
+}
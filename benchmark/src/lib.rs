@@ -1 +1,71 @@
-// this space intentionally left blank
+pub mod cache;
+#[cfg(feature = "profile")]
+pub mod profile;
+pub mod trace;
+
+use std::path::PathBuf;
+
+/// The workspace's `target` directory, where the runner keeps its files.
+pub fn target_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .join("target")
+}
+
+pub struct Day {
+    pub name: &'static str,
+    pub solve: fn() -> (String, String),
+    /// Entry points solving only one part, for days that compute the parts separately.
+    pub parts: Option<[fn() -> String; 2]>,
+}
+
+macro_rules! days {
+    (@parts $day:ident) => {
+        None
+    };
+
+    (@parts $day:ident parts) => {
+        Some([|| $day::part1().to_string(), || $day::part2().to_string()])
+    };
+
+    ($($day:ident $(: $parts:ident)?),+$(,)?) => {
+        pub const DAYS: &[Day] = &[$(Day {
+            name: stringify!($day),
+            solve: || {
+                let (p1, p2) = $day::solve();
+                (p1.to_string(), p2.to_string())
+            },
+            parts: days!(@parts $day $($parts)?),
+        }),+];
+    };
+}
+
+#[rustfmt::skip]
+days!(
+    day01: parts,
+    day02: parts,
+    day03: parts,
+    day04: parts,
+    day05: parts,
+    day06: parts,
+    day07: parts,
+    day08: parts,
+    day09: parts,
+    day10: parts,
+    day11: parts,
+    day12: parts,
+    day13: parts,
+    day14: parts,
+    day15: parts,
+    day16: parts,
+    day17: parts,
+    day18: parts,
+    day19: parts,
+    day20: parts,
+    day21: parts,
+    day22: parts,
+    day23: parts,
+    day24: parts,
+    day25: parts,
+);
@@ -0,0 +1,167 @@
+//! Print the answers for the given days, or for all of them.
+//!
+//! Answers are cached on disk (see [`aoc_benchmark::cache`]), unless `--no-cache` is given. Timing with `--time` or
+//! tracing always solves from scratch, as do the criterion and iai benchmarks, which call the solvers directly.
+//!
+//! When built with `--features trace`, `--trace` prints a per-phase timing tree after each day and
+//! `--chrome-trace <path>` writes a Chrome trace JSON file (see [`aoc_benchmark::trace`]).
+//!
+//! When built with `--features profile`, `aoc profile <day>` samples the day's solver in-process and writes a
+//! flamegraph (see `aoc_benchmark::profile`); build with `CARGO_PROFILE_RELEASE_DEBUG=true` to get symbols.
+use std::{env, path::PathBuf, process::ExitCode, time::Instant};
+
+use aoc_benchmark::{cache::Cache, trace::Tracer, DAYS};
+
+const USAGE: &str = "usage: aoc [--no-cache] [--time] [--trace] [--chrome-trace <path>] [days...]";
+
+const PROFILE_USAGE: &str = "usage: aoc profile <day> [--part 1|2] [--seconds <n>] [--frequency <hz>] [--out <dir>]";
+
+#[cfg(feature = "profile")]
+fn profile(args: &[String]) -> ExitCode {
+    use aoc_benchmark::profile::{profile, Options};
+    use std::time::Duration;
+
+    let mut options = Options {
+        part: None,
+        duration: Duration::from_secs(5),
+        frequency: 997,
+        out_dir: aoc_benchmark::target_dir().join("profile"),
+    };
+    let mut day = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let ok = match arg.as_str() {
+            "--part" => args
+                .next()
+                .and_then(|s| s.parse().ok())
+                .filter(|part| (1..=2).contains(part))
+                .map(|part| options.part = Some(part))
+                .is_some(),
+            "--seconds" => args
+                .next()
+                .and_then(|s| s.parse().ok())
+                .map(|secs| options.duration = Duration::from_secs_f64(secs))
+                .is_some(),
+            "--frequency" => args
+                .next()
+                .and_then(|s| s.parse().ok())
+                .map(|hz| options.frequency = hz)
+                .is_some(),
+            "--out" => args.next().map(|dir| options.out_dir = PathBuf::from(dir)).is_some(),
+            _ => day.replace(arg).is_none(),
+        };
+        if !ok {
+            eprintln!("{PROFILE_USAGE}");
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let Some(day) = day.and_then(|name| DAYS.iter().find(|day| day.name == *name)) else {
+        eprintln!("{PROFILE_USAGE}");
+        return ExitCode::FAILURE;
+    };
+
+    match profile(day, &options) {
+        Ok(profile) => {
+            println!(
+                "Profiled {} runs, wrote {} and {}.",
+                profile.runs,
+                profile.folded.display(),
+                profile.svg.display()
+            );
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(not(feature = "profile"))]
+fn profile(_args: &[String]) -> ExitCode {
+    eprintln!(
+        "{PROFILE_USAGE}
+profiling needs the runner to be built with `--features profile`"
+    );
+    ExitCode::FAILURE
+}
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    if args.first().is_some_and(|arg| arg == "profile") {
+        return profile(&args[1..]);
+    }
+
+    let mut use_cache = true;
+    let mut time = false;
+    let mut timing_tree = false;
+    let mut chrome_trace = None;
+    let mut selected = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--no-cache" => use_cache = false,
+            "--time" => time = true,
+            "--trace" => timing_tree = true,
+            "--chrome-trace" => match args.next() {
+                Some(path) => chrome_trace = Some(PathBuf::from(path)),
+                None => {
+                    eprintln!("{USAGE}");
+                    return ExitCode::FAILURE;
+                }
+            },
+            _ => selected.push(arg),
+        }
+    }
+
+    if let Some(unknown) = selected.iter().find(|s| !DAYS.iter().any(|day| day.name == *s)) {
+        eprintln!("{USAGE}\nunknown day {unknown:?}");
+        return ExitCode::FAILURE;
+    }
+
+    let tracer = match Tracer::init(timing_tree, chrome_trace) {
+        Ok(tracer) => tracer,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    use_cache &= !time && !tracer.is_enabled();
+    let mut cache = use_cache.then(Cache::load);
+
+    for day in DAYS
+        .iter()
+        .filter(|day| selected.is_empty() || selected.iter().any(|s| s == day.name))
+    {
+        let cached = cache.as_ref().and_then(|cache| cache.get(day.name)).cloned();
+        let ((p1, p2), note) = match cached {
+            Some(answers) => (answers, "cached".to_owned()),
+            None => {
+                let start = Instant::now();
+                let answers = tracer.solve(day);
+                let elapsed = start.elapsed();
+                if let Some(cache) = &mut cache {
+                    cache.insert(day.name, answers.clone());
+                }
+                (answers, if time { format!("{elapsed:?}") } else { String::new() })
+            }
+        };
+
+        if note.is_empty() {
+            println!("{}: {p1} {p2}", day.name);
+        } else {
+            println!("{}: {p1} {p2} ({note})", day.name);
+        }
+        tracer.report(day);
+    }
+
+    if let Some(cache) = cache {
+        if let Err(e) = cache.save() {
+            eprintln!("could not save the answer cache to {}: {e}", Cache::path().display());
+        }
+    }
+
+    ExitCode::SUCCESS
+}
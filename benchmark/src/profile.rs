@@ -0,0 +1,126 @@
+//! In-process sampling profiler: re-runs a day's solver under `pprof` for a while, then writes the folded stacks along
+//! with the flamegraph SVG rendered from them.
+use std::{
+    fmt, fs,
+    hint::black_box,
+    io::{self, BufWriter},
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use pprof::ProfilerGuardBuilder;
+
+use crate::Day;
+
+pub struct Options {
+    /// Which part to profile, 1 or 2, or both parts through `solve` if `None`.
+    pub part: Option<usize>,
+    /// How long to keep re-running the solver for; it always runs at least once.
+    pub duration: Duration,
+    /// Sampling frequency, in Hz.
+    pub frequency: i32,
+    pub out_dir: PathBuf,
+}
+
+pub struct Profile {
+    pub runs: usize,
+    pub folded: PathBuf,
+    pub svg: PathBuf,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    NoParts(&'static str),
+    NoSamples,
+    Pprof(pprof::Error),
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NoParts(day) => write!(
+                f,
+                "{day} solves both parts at once, so they can't be profiled separately"
+            ),
+            Error::NoSamples => write!(f, "no samples were collected, try profiling for longer"),
+            Error::Pprof(e) => write!(f, "{e}"),
+            Error::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<pprof::Error> for Error {
+    fn from(e: pprof::Error) -> Self {
+        Error::Pprof(e)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+pub fn profile(day: &Day, options: &Options) -> Result<Profile, Error> {
+    let (stem, solve): (String, Box<dyn Fn()>) = match options.part {
+        None => (day.name.to_owned(), Box::new(|| drop(black_box((day.solve)())))),
+        Some(part) => {
+            let solve = day.parts.ok_or(Error::NoParts(day.name))?[part - 1];
+            (
+                format!("{}-part{part}", day.name),
+                Box::new(move || drop(black_box(solve()))),
+            )
+        }
+    };
+
+    let guard = ProfilerGuardBuilder::default()
+        .frequency(options.frequency)
+        .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+        .build()?;
+    let start = Instant::now();
+    let mut runs = 0;
+    while runs == 0 || start.elapsed() < options.duration {
+        solve();
+        runs += 1;
+    }
+    let report = guard.report().build()?;
+    drop(guard);
+
+    // Same format as `pprof`'s own flamegraph output: thread, then frames from the outermost in, then the count.
+    let mut lines = report
+        .data
+        .iter()
+        .map(|(frames, count)| {
+            let mut line = frames.thread_name_or_id();
+            for symbol in frames.frames.iter().rev().flat_map(|frame| frame.iter().rev()) {
+                line.push(';');
+                line.push_str(&symbol.to_string());
+            }
+            format!("{line} {count}")
+        })
+        .collect::<Vec<_>>();
+    if lines.is_empty() {
+        return Err(Error::NoSamples);
+    }
+    lines.sort_unstable();
+
+    fs::create_dir_all(&options.out_dir)?;
+
+    let folded = options.out_dir.join(format!("{stem}.folded"));
+    fs::write(&folded, lines.join("\n") + "\n")?;
+
+    let svg = options.out_dir.join(format!("{stem}.svg"));
+    let mut flamegraph_options = inferno::flamegraph::Options::default();
+    flamegraph_options.title = format!("{stem} ({runs} runs)");
+    inferno::flamegraph::from_lines(
+        &mut flamegraph_options,
+        lines.iter().map(String::as_str),
+        BufWriter::new(fs::File::create(&svg)?),
+    )
+    .map_err(|e| io::Error::other(e.to_string()))?;
+
+    Ok(Profile { runs, folded, svg })
+}
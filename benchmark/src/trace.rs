@@ -0,0 +1,169 @@
+//! Collection of the solvers' per-phase spans (see [`aoc_trace`]), either as a timing tree printed after each day or as
+//! a Chrome trace JSON file, which can be opened in `chrome://tracing` or Perfetto.
+//!
+//! Without the `trace` feature the spans are compiled out of the solvers, and [`Tracer`] only solves.
+#[cfg(feature = "trace")]
+mod imp {
+    use std::{
+        path::PathBuf,
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    };
+
+    use tracing::{span, Subscriber};
+    use tracing_chrome::{ChromeLayerBuilder, FlushGuard};
+    use tracing_subscriber::{layer::Context, prelude::*, registry::LookupSpan, Layer};
+
+    use crate::Day;
+
+    struct Node {
+        name: &'static str,
+        parent: Option<usize>,
+        start: Instant,
+        elapsed: Option<Duration>,
+    }
+
+    /// Index of a span's node, stored in the span's extensions.
+    struct NodeIndex(usize);
+
+    /// Records the wall-clock duration of every span, so that they can be printed as a tree.
+    #[derive(Clone, Default)]
+    struct TimingTree(Arc<Mutex<Vec<Node>>>);
+
+    impl<S> Layer<S> for TimingTree
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        fn on_new_span(&self, _attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+            let span = ctx.span(id).unwrap();
+            let parent = span
+                .parent()
+                .and_then(|p| p.extensions().get::<NodeIndex>().map(|i| i.0));
+
+            let mut nodes = self.0.lock().unwrap();
+            // Spans opened on rayon's worker threads have no parent, so attach them to the innermost open root.
+            let parent = parent.or_else(|| nodes.iter().rposition(|n| n.parent.is_none() && n.elapsed.is_none()));
+            nodes.push(Node {
+                name: span.name(),
+                parent,
+                start: Instant::now(),
+                elapsed: None,
+            });
+            span.extensions_mut().insert(NodeIndex(nodes.len() - 1));
+        }
+
+        fn on_close(&self, id: span::Id, ctx: Context<'_, S>) {
+            let span = ctx.span(&id).unwrap();
+            let i = span.extensions().get::<NodeIndex>().unwrap().0;
+            let mut nodes = self.0.lock().unwrap();
+            nodes[i].elapsed = Some(nodes[i].start.elapsed());
+        }
+    }
+
+    impl TimingTree {
+        /// Print the recorded spans and forget about them.
+        fn print(&self, label: &str) {
+            let nodes = std::mem::take(&mut *self.0.lock().unwrap());
+            let roots = (0..nodes.len())
+                .filter(|&i| nodes[i].parent.is_none())
+                .collect::<Vec<_>>();
+            print_group(&nodes, label, &roots, 0);
+        }
+    }
+
+    /// Print a group of same-named sibling spans as one line, followed by their children.
+    fn print_group(nodes: &[Node], name: &str, members: &[usize], depth: usize) {
+        let total = members.iter().filter_map(|&i| nodes[i].elapsed).sum::<Duration>();
+        let label = if members.len() > 1 {
+            format!("{name} (x{})", members.len())
+        } else {
+            name.to_owned()
+        };
+        println!(
+            "{:indent$}{label:<width$} {total:>10.2?}",
+            "",
+            indent = 2 * depth,
+            width = 40 - 2 * depth
+        );
+
+        let mut groups: Vec<(&str, Vec<usize>)> = Vec::new();
+        for (i, node) in nodes.iter().enumerate() {
+            if !node.parent.is_some_and(|p| members.contains(&p)) {
+                continue;
+            }
+            match groups.iter_mut().find(|(name, _)| *name == node.name) {
+                Some((_, group)) => group.push(i),
+                None => groups.push((node.name, vec![i])),
+            }
+        }
+        for (name, group) in groups {
+            print_group(nodes, name, &group, depth + 1);
+        }
+    }
+
+    /// Solves days, recording their spans.
+    pub struct Tracer {
+        timing: Option<TimingTree>,
+        chrome: Option<FlushGuard>,
+    }
+
+    impl Tracer {
+        /// Set up the global subscriber, printing a timing tree after each day if `timing` is set and writing a Chrome
+        /// trace to `chrome` if given.
+        pub fn init(timing: bool, chrome: Option<PathBuf>) -> Result<Self, &'static str> {
+            let timing = timing.then(TimingTree::default);
+            let (chrome, guard) = chrome
+                .map(|path| ChromeLayerBuilder::new().file(path).include_args(true).build())
+                .unzip();
+            tracing_subscriber::registry().with(timing.clone()).with(chrome).init();
+            Ok(Self { timing, chrome: guard })
+        }
+
+        pub fn is_enabled(&self) -> bool {
+            self.timing.is_some() || self.chrome.is_some()
+        }
+
+        pub fn solve(&self, day: &Day) -> (String, String) {
+            tracing::info_span!("solve", day = day.name).in_scope(day.solve)
+        }
+
+        /// Print the timing tree of the last solved day, if one is being recorded.
+        pub fn report(&self, day: &Day) {
+            if let Some(timing) = &self.timing {
+                timing.print(day.name);
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "trace"))]
+mod imp {
+    use std::path::PathBuf;
+
+    use crate::Day;
+
+    /// Stand-in for the tracer, which only solves.
+    pub struct Tracer;
+
+    impl Tracer {
+        pub fn init(timing: bool, chrome: Option<PathBuf>) -> Result<Self, &'static str> {
+            if timing || chrome.is_some() {
+                Err("tracing needs the runner to be built with `--features trace`")
+            } else {
+                Ok(Self)
+            }
+        }
+
+        pub fn is_enabled(&self) -> bool {
+            false
+        }
+
+        pub fn solve(&self, day: &Day) -> (String, String) {
+            (day.solve)()
+        }
+
+        pub fn report(&self, _day: &Day) {}
+    }
+}
+
+pub use imp::Tracer;
@@ -1,25 +1,101 @@
 use std::{fmt::Display, iter::zip};
 
+/// Evaluate `$body` with the two lists sorted, as `u32`s, which sort faster, if all the IDs fit, or as `u64`s if not.
+macro_rules! with_lists {
+    ($input:expr, |$left:ident, $right:ident| $body:expr) => {
+        match aoc_trace::span!("parse", aoc_parse::columns::<u32>($input)) {
+            Some((mut $left, mut $right)) => {
+                sort(&mut $left, &mut $right);
+                $body
+            }
+            None => {
+                let (mut $left, mut $right) = aoc_trace::span!("parse", aoc_parse::columns::<u64>($input).unwrap());
+                sort(&mut $left, &mut $right);
+                $body
+            }
+        }
+    };
+}
+
 #[inline]
 pub fn solve() -> (impl Display, impl Display) {
-    let input = include_str!("input.txt");
+    solve_input(include_str!(concat!(env!("OUT_DIR"), "/input.txt")))
+}
 
-    let (mut left, mut right): (Vec<u32>, Vec<u32>) = input
-        .lines()
-        .map(|line| line.split_once(' ').unwrap())
-        .map(|(a, b)| (a.parse::<u32>().unwrap(), b.trim().parse::<u32>().unwrap()))
-        .unzip();
+pub fn solve_input(input: &str) -> (impl Display, impl Display) {
+    with_lists!(input, |left, right| (
+        aoc_trace::span!("part1", distance(&left, &right)),
+        aoc_trace::span!("part2", similarity(&left, &right))
+    ))
+}
 
-    left.sort_unstable();
-    right.sort_unstable();
+pub fn part1() -> impl Display {
+    with_lists!(include_str!(concat!(env!("OUT_DIR"), "/input.txt")), |left, right| {
+        distance(&left, &right)
+    })
+}
+
+pub fn part2() -> impl Display {
+    with_lists!(include_str!(concat!(env!("OUT_DIR"), "/input.txt")), |left, right| {
+        similarity(&left, &right)
+    })
+}
+
+fn sort<T: Ord>(left: &mut [T], right: &mut [T]) {
+    aoc_trace::span!("sort", {
+        left.sort_unstable();
+        right.sort_unstable();
+    });
+}
+
+/// Sum of the differences between the sorted lists' values, pair by pair.
+fn distance<T: Copy + Into<u64>>(left: &[T], right: &[T]) -> u128 {
+    zip(left, right)
+        .map(|(&a, &b)| u128::from(a.into().abs_diff(b.into())))
+        .sum()
+}
+
+/// Sum of each left value times how often it appears on the right, by walking the runs of equal values in both sorted
+/// lists side by side.
+fn similarity<T: Copy + Ord + Into<u64>>(left: &[T], right: &[T]) -> u128 {
+    let mut p2 = 0;
+    let (mut i, mut j) = (0, 0);
+    while i < left.len() && j < right.len() {
+        let n = left[i];
+        if n < right[j] {
+            i += 1;
+        } else if n > right[j] {
+            j += 1;
+        } else {
+            let left_run = left[i..].iter().take_while(|&&m| m == n).count();
+            let right_run = right[j..].iter().take_while(|&&m| m == n).count();
+            p2 += u128::from(n.into()) * (left_run * right_run) as u128;
+            i += left_run;
+            j += right_run;
+        }
+    }
+    p2
+}
 
-    let p1 = zip(left.iter(), right.iter())
-        .map(|(&a, &b)| a.abs_diff(b))
-        .sum::<u32>();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let mut freq = vec![0; 100_000];
-    right.iter().for_each(|&n| freq[n as usize] += 1);
-    let p2 = left.iter().map(|n| n * freq[*n as usize]).sum::<u32>();
+    #[test]
+    fn test_example() {
+        let (p1, p2) = solve_input("3   4\n4   3\n2   5\n1   3\n3   9\n3   3\n");
+        assert_eq!(p1.to_string(), "11");
+        assert_eq!(p2.to_string(), "31");
+    }
 
-    (p1, p2)
+    #[test]
+    fn test_large_ids() {
+        let (p1, p2) =
+            solve_input("100000   4294967295\n4294967295   100000\n18446744073709551615   18446744073709551615\n");
+        assert_eq!(p1.to_string(), "0");
+        assert_eq!(
+            p2.to_string(),
+            (100_000u128 + 4_294_967_295 + 18_446_744_073_709_551_615).to_string()
+        );
+    }
 }
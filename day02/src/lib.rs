@@ -1,45 +1,349 @@
-use std::fmt::Display;
+use std::{
+    cmp::{Ordering, Reverse},
+    fmt::{self, Display},
+    ops::RangeInclusive,
+};
 
-use arrayvec::ArrayVec;
 use rayon::prelude::*;
 
-fn is_safe(report: &[u8]) -> bool {
-    let cond1 = report.windows(2).all(|w| w[0] <= w[1]) || report.windows(2).all(|w| w[0] >= w[1]);
+/// What a report's levels must look like for it to be safe.
+#[derive(Clone, Debug)]
+pub struct Rules {
+    /// By how much each level may differ from the previous one, in the direction the report goes in.
+    pub steps: RangeInclusive<u32>,
+    /// How many levels the Problem Dampener may remove.
+    pub tolerance: usize,
+}
+
+impl Rules {
+    pub const PART1: Rules = Rules {
+        steps: 1..=3,
+        tolerance: 0,
+    };
+
+    pub const PART2: Rules = Rules {
+        steps: 1..=3,
+        tolerance: 1,
+    };
+
+    /// The fewest levels that must be removed from `report` to make it safe, if that's within the tolerance.
+    pub fn removals(&self, report: &[u32]) -> Option<usize> {
+        self.removed_levels(report).map(|removed| removed.len())
+    }
+
+    /// The indices of the fewest levels that must be removed from `report` to make it safe, if there are few enough.
+    pub fn removed_levels(&self, report: &[u32]) -> Option<Vec<usize>> {
+        let increasing = self.removed_towards(report, |a, b| b.checked_sub(a));
+        let decreasing = self.removed_towards(report, |a, b| a.checked_sub(b));
+        increasing.into_iter().chain(decreasing).min_by_key(Vec::len)
+    }
+
+    /// Like [`Rules::removed_levels`], for a report going in the direction in which `step` measures the steps.
+    ///
+    /// The levels that are kept must form a chain of valid steps, and as at most `tolerance` levels can be removed, each
+    /// kept level is at most `tolerance + 1` places after the previous one: keeping track of the fewest removals needed
+    /// for a chain ending at each level makes this `O(n * tolerance)`.
+    fn removed_towards(&self, report: &[u32], step: impl Fn(u32, u32) -> Option<u32>) -> Option<Vec<usize>> {
+        if report.is_empty() {
+            return Some(Vec::new());
+        }
+
+        // The fewest removals for a chain ending at each level, along with the previous level in that chain. Ties go to the
+        // chains that keep later levels, so that the earliest levels are the ones removed, as in the puzzle's examples.
+        let mut chains: Vec<(usize, Option<usize>)> = Vec::with_capacity(report.len());
+        for (i, &level) in report.iter().enumerate() {
+            let chain = (i.saturating_sub(self.tolerance + 1)..i)
+                .filter(|&j| step(report[j], level).is_some_and(|d| self.steps.contains(&d)))
+                .map(|j| (chains[j].0 + (i - j - 1), Some(j)))
+                .chain([(i, None)])
+                .min_by_key(|&(removed, previous)| (removed, Reverse(previous)))
+                .unwrap();
+            chains.push(chain);
+        }
 
-    let cond2 = report.windows(2).all(|w| {
-        let d = w[0].abs_diff(w[1]);
-        (1..=3).contains(&d)
-    });
+        let (fewest, last) = chains
+            .iter()
+            .enumerate()
+            .map(|(i, &(removed, _))| (removed + (report.len() - 1 - i), i))
+            .min_by_key(|&(removed, i)| (removed, Reverse(i)))
+            .unwrap();
+        if fewest > self.tolerance {
+            return None;
+        }
 
-    cond1 && cond2
+        let mut kept = vec![false; report.len()];
+        let mut level = Some(last);
+        while let Some(i) = level {
+            kept[i] = true;
+            level = chains[i].1;
+        }
+        Some((0..report.len()).filter(|&i| !kept[i]).collect())
+    }
 }
 
-#[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    let input = include_str!("input.txt");
+pub fn is_safe(report: &[u32], rules: &Rules) -> bool {
+    rules.removals(report).is_some()
+}
 
-    let reports = input
-        .par_lines()
-        .map(|line| {
-            line.split(' ')
-                .map(|n| n.parse::<u8>().unwrap())
-                .collect::<ArrayVec<_, 8>>()
+/// The first pair of neighbouring levels that breaks the rules, without the dampener.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Problem {
+    /// The levels at `index` and `index + 1` go the other way from the ones before them.
+    DirectionChange { index: usize },
+    /// The levels at `index` and `index + 1` differ by `step`, which is out of range.
+    Step { index: usize, step: u32 },
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Problem::DirectionChange { index } => write!(f, "levels {index} and {} change direction", index + 1),
+            Problem::Step { index, step } => write!(f, "levels {index} and {} differ by {step}", index + 1),
+        }
+    }
+}
+
+pub fn first_problem(report: &[u32], steps: &RangeInclusive<u32>) -> Option<Problem> {
+    let mut direction = None;
+    report.windows(2).enumerate().find_map(|(index, pair)| {
+        let order = pair[0].cmp(&pair[1]);
+        if order != Ordering::Equal && *direction.get_or_insert(order) != order {
+            return Some(Problem::DirectionChange { index });
+        }
+        let step = pair[0].abs_diff(pair[1]);
+        (!steps.contains(&step)).then_some(Problem::Step { index, step })
+    })
+}
+
+/// Why a report was classified the way it was.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Safe,
+    /// Unsafe because of `problem`, but safe once the levels at the indices in `removed` are removed.
+    Dampened {
+        problem: Problem,
+        removed: Vec<usize>,
+    },
+    Unsafe(Problem),
+}
+
+pub fn explain(report: &[u32], rules: &Rules) -> Verdict {
+    let Some(problem) = first_problem(report, &rules.steps) else {
+        return Verdict::Safe;
+    };
+    match rules.removed_levels(report) {
+        Some(removed) => Verdict::Dampened { problem, removed },
+        None => Verdict::Unsafe(problem),
+    }
+}
+
+pub struct Explanation {
+    /// Line number of the report, from 1.
+    pub line: usize,
+    pub report: Vec<u32>,
+    pub verdict: Verdict,
+}
+
+/// The verdict on every report in the input.
+pub fn explain_input(input: &str, rules: &Rules) -> Vec<Explanation> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(i, line)| {
+            let report = aoc_parse::uints(line).collect::<Vec<_>>();
+            let verdict = explain(&report, rules);
+            Explanation {
+                line: i + 1,
+                report,
+                verdict,
+            }
+        })
+        .collect()
+}
+
+fn join(levels: &[impl Display], separator: &str) -> String {
+    levels
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// Render explanations as a table with one row per report; levels are numbered from 0, as in the JSON.
+pub fn explanation_table(explanations: &[Explanation]) -> String {
+    let width = explanations
+        .iter()
+        .map(|explanation| join(&explanation.report, " ").len())
+        .max()
+        .unwrap_or(0)
+        .max("levels".len());
+
+    let mut out = format!("{:>5}  {:<8}  {:<width$}  reason\n", "line", "verdict", "levels");
+    for Explanation { line, report, verdict } in explanations {
+        let (name, reason) = match verdict {
+            Verdict::Safe => ("safe", String::new()),
+            Verdict::Dampened { problem, removed } => (
+                "dampened",
+                format!("{problem}, safe without level {}", join(removed, " and ")),
+            ),
+            Verdict::Unsafe(problem) => ("unsafe", problem.to_string()),
+        };
+        let row = format!("{line:>5}  {name:<8}  {:<width$}  {reason}", join(report, " "));
+        out += row.trim_end();
+        out.push('\n');
+    }
+    out
+}
+
+/// Render explanations as a JSON array with one object per report.
+pub fn explanation_json(explanations: &[Explanation]) -> String {
+    let problem_json = |problem: &Problem| match *problem {
+        Problem::DirectionChange { index } => format!(r#"{{"kind": "direction_change", "index": {index}}}"#),
+        Problem::Step { index, step } => format!(r#"{{"kind": "step", "index": {index}, "step": {step}}}"#),
+    };
+
+    let objects = explanations
+        .iter()
+        .map(|Explanation { line, report, verdict }| {
+            let fields = match verdict {
+                Verdict::Safe => r#""verdict": "safe""#.to_owned(),
+                Verdict::Dampened { problem, removed } => format!(
+                    r#""verdict": "dampened", "problem": {}, "removed": [{}]"#,
+                    problem_json(problem),
+                    join(removed, ", ")
+                ),
+                Verdict::Unsafe(problem) => format!(r#""verdict": "unsafe", "problem": {}"#, problem_json(problem)),
+            };
+            format!(r#"  {{"line": {line}, "levels": [{}], {fields}}}"#, join(report, ", "))
         })
         .collect::<Vec<_>>();
+    format!("[\n{}\n]\n", objects.join(",\n"))
+}
 
-    let p1 = reports.par_iter().filter(|report| is_safe(report)).count();
-
-    let p2 = reports
-        .into_par_iter()
-        .filter(|report| {
-            is_safe(report)
-                || (0..report.len()).any(|i| {
-                    let mut r = report.clone();
-                    r.remove(i);
-                    is_safe(&r)
-                })
+/// Run `f` on every report in the input.
+fn reports<'a, T: Send + 'a>(
+    input: &'a str,
+    f: impl Fn(&[u32]) -> T + Sync + Send + 'a,
+) -> impl ParallelIterator<Item = T> + 'a {
+    input
+        .par_lines()
+        .filter(|line| !line.is_empty())
+        .map_init(Vec::new, move |report, line| {
+            report.clear();
+            report.extend(aoc_parse::uints::<u32>(line));
+            f(report)
         })
-        .count();
+}
+
+/// How many of the reports in the input are safe under the given rules.
+pub fn count_safe(input: &str, rules: &Rules) -> usize {
+    reports(input, |report| is_safe(report, rules))
+        .filter(|&safe| safe)
+        .count()
+}
+
+#[inline]
+pub fn solve() -> (impl Display, impl Display) {
+    solve_input(include_str!(concat!(env!("OUT_DIR"), "/input.txt")))
+}
+
+pub fn solve_input(input: &str) -> (impl Display, impl Display) {
+    // A report is safe without the dampener if the dampener wouldn't need to remove anything.
+    aoc_trace::span!("reports");
+    reports(input, |report| match Rules::PART2.removals(report) {
+        Some(0) => (1, 1),
+        Some(_) => (0, 1),
+        None => (0, 0),
+    })
+    .reduce(|| (0, 0), |(a1, a2), (b1, b2)| (a1 + b1, a2 + b2))
+}
+
+pub fn part1() -> impl Display {
+    count_safe(include_str!(concat!(env!("OUT_DIR"), "/input.txt")), &Rules::PART1)
+}
+
+pub fn part2() -> impl Display {
+    count_safe(include_str!(concat!(env!("OUT_DIR"), "/input.txt")), &Rules::PART2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "7 6 4 2 1\n1 2 7 8 9\n9 7 6 2 1\n1 3 2 4 5\n8 6 4 4 1\n1 3 6 7 9\n";
+
+    #[test]
+    fn test_example() {
+        let (p1, p2) = solve_input(EXAMPLE);
+        assert_eq!((p1.to_string(), p2.to_string()), ("2".to_owned(), "4".to_owned()));
+    }
+
+    #[test]
+    fn test_rules() {
+        let rules = |steps, tolerance| Rules { steps, tolerance };
+        assert_eq!(count_safe(EXAMPLE, &rules(1..=5, 0)), 4);
+        assert_eq!(count_safe(EXAMPLE, &rules(1..=3, 2)), 6);
+        assert_eq!(count_safe(EXAMPLE, &rules(0..=3, 0)), 3);
+
+        let long = (0..1000).map(|i| (i * 2).to_string()).collect::<Vec<_>>().join(" ");
+        assert_eq!(count_safe(&long, &Rules::PART1), 1);
+        assert_eq!(count_safe(&long, &rules(3..=4, 500)), 1);
+        assert_eq!(count_safe(&long, &rules(3..=4, 499)), 0);
+
+        // Removing the first or last level, or several in a row.
+        let report = [10, 1, 2, 3, 4, 50];
+        assert_eq!(Rules::PART2.removals(&report), None);
+        assert_eq!(rules(1..=3, 2).removals(&report), Some(2));
+        assert_eq!(rules(1..=3, 3).removals(&[1, 2, 9, 9, 9, 3]), Some(3));
+    }
+
+    #[test]
+    fn test_explain() {
+        let verdicts = explain_input(EXAMPLE, &Rules::PART2)
+            .into_iter()
+            .map(|explanation| explanation.verdict)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            verdicts,
+            [
+                Verdict::Safe,
+                Verdict::Unsafe(Problem::Step { index: 1, step: 5 }),
+                Verdict::Unsafe(Problem::Step { index: 2, step: 4 }),
+                Verdict::Dampened {
+                    problem: Problem::DirectionChange { index: 1 },
+                    removed: vec![1]
+                },
+                Verdict::Dampened {
+                    problem: Problem::Step { index: 2, step: 0 },
+                    removed: vec![2]
+                },
+                Verdict::Safe,
+            ]
+        );
+        assert!(verdicts
+            .iter()
+            .zip(reports(EXAMPLE, |report| is_safe(report, &Rules::PART2)).collect::<Vec<_>>())
+            .all(|(verdict, safe)| matches!(verdict, Verdict::Unsafe(_)) != safe));
 
-    (p1, p2)
+        let explanations = explain_input("1 3 2 4 5\n\n9 7 6 2 1\n", &Rules::PART2);
+        assert_eq!(
+            explanation_table(&explanations),
+            [
+                " line  verdict   levels     reason",
+                "    1  dampened  1 3 2 4 5  levels 1 and 2 change direction, safe without level 1",
+                "    3  unsafe    9 7 6 2 1  levels 2 and 3 differ by 4",
+                "",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            explanation_json(&explanations),
+            r#"[
+  {"line": 1, "levels": [1, 3, 2, 4, 5], "verdict": "dampened", "problem": {"kind": "direction_change", "index": 1}, "removed": [1]},
+  {"line": 3, "levels": [9, 7, 6, 2, 1], "verdict": "unsafe", "problem": {"kind": "step", "index": 2, "step": 4}}
+]
+"#
+        );
+    }
 }
@@ -1,5 +1,26 @@
-fn main() {
-    let (part1, part2) = day02::solve();
-    println!("{part1}");
-    println!("{part2}");
+use std::{env, process::ExitCode};
+
+use day02::Rules;
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let explain = |render: fn(&[day02::Explanation]) -> String| {
+        let input = include_str!(concat!(env!("OUT_DIR"), "/input.txt"));
+        print!("{}", render(&day02::explain_input(input, &Rules::PART2)));
+    };
+
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [] => {
+            let (part1, part2) = day02::solve();
+            println!("{part1}");
+            println!("{part2}");
+        }
+        ["--explain"] => explain(day02::explanation_table),
+        ["--explain", "--json"] => explain(day02::explanation_json),
+        _ => {
+            eprintln!("usage: day02 [--explain [--json]]");
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
 }
@@ -1,31 +1,548 @@
-use std::fmt::Display;
+use std::{
+    fmt::{self, Display},
+    io::{self, BufReader, Bytes, Read},
+};
 
-#[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    let input = include_str!("input.txt");
-
-    let re2 = regex::Regex::new(r"do\(\)|don't\(\)|mul\(([0-9]{1,3}),([0-9]{1,3})\)").unwrap();
-
-    let mut p1 = 0;
-    let mut p2 = 0;
-
-    let mut enabled = true;
-
-    for cap in re2.captures_iter(input) {
-        match cap.get(0).unwrap().as_str() {
-            "do()" => enabled = true,
-            "don't()" => enabled = false,
-            _ => {
-                let a: u32 = cap[1].parse().unwrap();
-                let b: u32 = cap[2].parse().unwrap();
-                let c = a * b;
-                p1 += c;
-                if enabled {
-                    p2 += c;
+/// Arguments are 1 to 3 digits long.
+const MAX_DIGITS: usize = 3;
+
+/// Most arguments an instruction can take.
+pub const MAX_ARITY: usize = 4;
+
+/// What an instruction looks like in memory, `name(arg,...)`, and what it does.
+#[derive(Clone, Copy, Debug)]
+pub struct Spec {
+    pub name: &'static str,
+    pub arity: usize,
+    pub execute: fn(&mut Machine, &[u32]),
+}
+
+impl PartialEq for Spec {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.arity == other.arity
+    }
+}
+
+impl Eq for Spec {}
+
+const DO: Spec = Spec {
+    name: "do",
+    arity: 0,
+    execute: |machine, _| machine.enabled = true,
+};
+
+const DONT: Spec = Spec {
+    name: "don't",
+    arity: 0,
+    execute: |machine, _| machine.enabled = false,
+};
+
+const MUL: Spec = Spec {
+    name: "mul",
+    arity: 2,
+    execute: |machine, args| machine.accumulate(u64::from(args[0]) * u64::from(args[1])),
+};
+
+/// The instructions the scanner looks for, in the order in which they're tried.
+#[derive(Clone, Debug)]
+pub struct InstructionSet {
+    specs: Vec<Spec>,
+}
+
+impl Default for InstructionSet {
+    /// The instructions of the puzzle: `do()`, `don't()` and `mul(a,b)`.
+    fn default() -> Self {
+        Self {
+            specs: vec![DO, DONT, MUL],
+        }
+    }
+}
+
+impl InstructionSet {
+    /// Add an instruction, tried after those already in the set; the scanner yields it as [`Instruction::Other`].
+    pub fn register(&mut self, spec: Spec) {
+        assert!(
+            !spec.name.is_empty() && !spec.name.contains(['(', ')', ',']),
+            "invalid instruction name {:?}",
+            spec.name
+        );
+        assert!(
+            spec.arity <= MAX_ARITY,
+            "{} takes more than {MAX_ARITY} arguments",
+            spec.name
+        );
+        self.specs.push(spec);
+    }
+
+    fn decode(&self, pattern: usize, args: [u32; MAX_ARITY]) -> Instruction {
+        // The puzzle's own instructions always come first.
+        match pattern {
+            0 => Instruction::Do,
+            1 => Instruction::Dont,
+            2 => Instruction::Mul(args[0], args[1]),
+            _ => Instruction::Other(self.specs[pattern], args),
+        }
+    }
+}
+
+/// An instruction found in the corrupted memory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Instruction {
+    Mul(u32, u32),
+    Do,
+    Dont,
+    /// A registered instruction, with its arguments followed by zeros.
+    Other(Spec, [u32; MAX_ARITY]),
+}
+
+impl Instruction {
+    fn spec(&self) -> Spec {
+        match *self {
+            Self::Mul(..) => MUL,
+            Self::Do => DO,
+            Self::Dont => DONT,
+            Self::Other(spec, _) => spec,
+        }
+    }
+
+    pub fn args(&self) -> Vec<u32> {
+        match *self {
+            Self::Mul(a, b) => vec![a, b],
+            Self::Do | Self::Dont => vec![],
+            Self::Other(spec, args) => args[..spec.arity].to_vec(),
+        }
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let args = self.args().iter().map(u32::to_string).collect::<Vec<_>>();
+        f.pad(&format!("{}({})", self.spec().name, args.join(",")))
+    }
+}
+
+/// How far the window's bytes got in matching the patterns.
+enum Status {
+    /// A pattern matched the first `len` bytes.
+    Match {
+        len: usize,
+        pattern: usize,
+        args: [u32; MAX_ARITY],
+    },
+    /// The window is the start of a match, so far.
+    Partial,
+    Fail,
+}
+
+/// Match an instruction against the start of `window`.
+fn match_pattern(window: &[u8], spec: &Spec, pattern: usize) -> Status {
+    let mut bytes = window.iter().copied().enumerate();
+
+    for &expected in spec.name.as_bytes().iter().chain(b"(") {
+        match bytes.next() {
+            Some((_, b)) if b == expected => {}
+            Some(_) => return Status::Fail,
+            None => return Status::Partial,
+        }
+    }
+
+    let arity = spec.arity;
+    let mut args = [0; MAX_ARITY];
+    for (i, arg) in args.iter_mut().enumerate().take(arity) {
+        let mut digits = 0;
+        let terminator = if i + 1 == arity { b')' } else { b',' };
+        loop {
+            match bytes.next() {
+                Some((_, b)) if b.is_ascii_digit() && digits < MAX_DIGITS => {
+                    *arg = *arg * 10 + u32::from(b - b'0');
+                    digits += 1;
+                }
+                Some((end, b)) if b == terminator && digits > 0 => {
+                    if i + 1 == arity {
+                        return Status::Match {
+                            len: end + 1,
+                            pattern,
+                            args,
+                        };
+                    }
+                    break;
+                }
+                Some(_) => return Status::Fail,
+                None => return Status::Partial,
+            }
+        }
+    }
+
+    match bytes.next() {
+        Some((end, b')')) => Status::Match {
+            len: end + 1,
+            pattern,
+            args,
+        },
+        Some(_) => Status::Fail,
+        None => Status::Partial,
+    }
+}
+
+/// Match the instructions against the start of `window`, the first one to match winning.
+fn match_patterns(window: &[u8], set: &InstructionSet) -> Status {
+    let mut partial = false;
+    for (pattern, spec) in set.specs.iter().enumerate() {
+        match match_pattern(window, spec, pattern) {
+            status @ Status::Match { .. } => return status,
+            Status::Partial => partial = true,
+            Status::Fail => {}
+        }
+    }
+    if partial {
+        Status::Partial
+    } else {
+        Status::Fail
+    }
+}
+
+/// Scans corrupted memory for instructions, yielding them along with the byte offset at which they start.
+///
+/// With the default instruction set, the matches are the same as those of the regex
+/// `do\(\)|don't\(\)|mul\(([0-9]{1,3}),([0-9]{1,3})\)`: a byte at a time, the scanner keeps a window of the bytes that
+/// may be the start of a match, which is never longer than the longest possible match. When the window can't be the
+/// start of a match anymore, its first byte is dropped and the rest re-examined, exactly like a regex engine would move
+/// on to the next starting position.
+pub struct Scanner<'a, R> {
+    set: &'a InstructionSet,
+    bytes: Bytes<BufReader<R>>,
+    window: Vec<u8>,
+    /// Offset of the start of the window.
+    offset: usize,
+    eof: bool,
+}
+
+impl<'a, R: Read> Scanner<'a, R> {
+    pub fn new(reader: R, set: &'a InstructionSet) -> Self {
+        Self {
+            set,
+            bytes: BufReader::new(reader).bytes(),
+            window: Vec::new(),
+            offset: 0,
+            eof: false,
+        }
+    }
+}
+
+impl<R: Read> Iterator for Scanner<'_, R> {
+    type Item = io::Result<(usize, Instruction)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if !self.window.is_empty() {
+                match match_patterns(&self.window, self.set) {
+                    Status::Match { len, pattern, args } => {
+                        let offset = self.offset;
+                        self.window.drain(..len);
+                        self.offset += len;
+                        return Some(Ok((offset, self.set.decode(pattern, args))));
+                    }
+                    // At the end of the input, a partial match is as good as a failed one.
+                    Status::Partial if !self.eof => {}
+                    Status::Partial | Status::Fail => {
+                        self.window.remove(0);
+                        self.offset += 1;
+                        continue;
+                    }
                 }
             }
+
+            match self.bytes.next() {
+                Some(Ok(b)) => self.window.push(b),
+                Some(Err(e)) => return Some(Err(e)),
+                None if self.window.is_empty() => return None,
+                None => self.eof = true,
+            }
         }
     }
+}
+
+/// The state the instructions act upon.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Machine {
+    pub enabled: bool,
+    /// Sum of every result, enabled or not.
+    pub total: u64,
+    /// Sum of the results produced while enabled.
+    pub enabled_total: u64,
+}
+
+impl Default for Machine {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            total: 0,
+            enabled_total: 0,
+        }
+    }
+}
+
+/// One instruction executed by the machine, with the totals right after it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Step {
+    pub offset: usize,
+    pub instruction: Instruction,
+    /// Whether the machine was enabled when the instruction was executed.
+    pub enabled: bool,
+    pub total: u64,
+    pub enabled_total: u64,
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>8}  {:<16}  {:<3}  {:>12}  {:>12}",
+            self.offset,
+            self.instruction,
+            if self.enabled { "on" } else { "off" },
+            self.total,
+            self.enabled_total
+        )
+    }
+}
+
+impl Machine {
+    /// Add the result of an instruction to the totals.
+    pub fn accumulate(&mut self, value: u64) {
+        self.total += value;
+        if self.enabled {
+            self.enabled_total += value;
+        }
+    }
+
+    pub fn execute(&mut self, instruction: &Instruction) {
+        match *instruction {
+            Instruction::Mul(a, b) => (MUL.execute)(self, &[a, b]),
+            Instruction::Do => (DO.execute)(self, &[]),
+            Instruction::Dont => (DONT.execute)(self, &[]),
+            Instruction::Other(spec, args) => (spec.execute)(self, &args[..spec.arity]),
+        }
+    }
+
+    /// Execute every instruction the scanner finds, handing each step to `trace`.
+    pub fn run<R: Read>(&mut self, scanner: Scanner<R>, mut trace: impl FnMut(&Step)) -> io::Result<()> {
+        for found in scanner {
+            let (offset, instruction) = found?;
+            let enabled = self.enabled;
+            self.execute(&instruction);
+            trace(&Step {
+                offset,
+                instruction,
+                enabled,
+                total: self.total,
+                enabled_total: self.enabled_total,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Every step of running the input with the default instruction set.
+pub fn trace(input: &str) -> Vec<Step> {
+    let mut steps = Vec::new();
+    Machine::default()
+        .run(Scanner::new(input.as_bytes(), &InstructionSet::default()), |step| {
+            steps.push(*step)
+        })
+        .unwrap();
+    steps
+}
+
+#[inline]
+pub fn solve() -> (impl Display, impl Display) {
+    solve_input(include_str!(concat!(env!("OUT_DIR"), "/input.txt")))
+}
+
+pub fn solve_input(input: &str) -> (impl Display, impl Display) {
+    let machine = run(input);
+    (machine.total, machine.enabled_total)
+}
+
+// Both parts come out of the same scan; `do()` and `don't()` only decide which of the totals a product goes into.
+pub fn part1() -> impl Display {
+    run(include_str!(concat!(env!("OUT_DIR"), "/input.txt"))).total
+}
 
-    (p1, p2)
+pub fn part2() -> impl Display {
+    run(include_str!(concat!(env!("OUT_DIR"), "/input.txt"))).enabled_total
+}
+
+fn run(input: &str) -> Machine {
+    aoc_trace::span!("scan");
+
+    let mut machine = Machine::default();
+    machine
+        .run(Scanner::new(input.as_bytes(), &InstructionSet::default()), |_| {})
+        .unwrap();
+    machine
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::LazyLock;
+
+    use rand::prelude::*;
+
+    use super::*;
+
+    /// The regex the scanner replaced.
+    static REGEX: LazyLock<regex::Regex> =
+        LazyLock::new(|| regex::Regex::new(r"do\(\)|don't\(\)|mul\(([0-9]{1,3}),([0-9]{1,3})\)").unwrap());
+
+    fn regex_matches(input: &str) -> Vec<(usize, Instruction)> {
+        REGEX
+            .captures_iter(input)
+            .map(|cap| {
+                let m = match cap.get(0).unwrap().as_str() {
+                    "do()" => Instruction::Do,
+                    "don't()" => Instruction::Dont,
+                    _ => Instruction::Mul(cap[1].parse().unwrap(), cap[2].parse().unwrap()),
+                };
+                (cap.get(0).unwrap().start(), m)
+            })
+            .collect()
+    }
+
+    fn scanner_matches(reader: impl Read) -> Vec<(usize, Instruction)> {
+        Scanner::new(reader, &InstructionSet::default())
+            .collect::<io::Result<_>>()
+            .unwrap()
+    }
+
+    /// A reader that hands out one byte per read.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let Some((&b, rest)) = self.0.split_first() else {
+                return Ok(0);
+            };
+            buf[0] = b;
+            self.0 = rest;
+            Ok(1)
+        }
+    }
+
+    fn assert_same_matches(input: &str) -> Vec<(usize, Instruction)> {
+        let expected = regex_matches(input);
+        assert_eq!(scanner_matches(input.as_bytes()), expected, "{input:?}");
+        assert_eq!(scanner_matches(Trickle(input.as_bytes())), expected, "{input:?}");
+        expected
+    }
+
+    #[test]
+    fn test_examples() {
+        let (p1, p2) = solve_input("xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))");
+        assert_eq!((p1.to_string(), p2.to_string()), ("161".to_owned(), "48".to_owned()));
+
+        for input in [
+            "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))",
+            "mul(mul(2,3)",
+            "mumul(1,2)mul(1,mul(3,4)",
+            "mul(1234,5)mul(12,3456)mul(123,456)mul(0,0)mul(,1)mul(1,)mul(1 ,2)",
+            "dodo()don'don't()don't(do()don't()mul(1,2",
+            "mul(1,2)mul(3,4)",
+            "",
+            "m",
+        ] {
+            assert_same_matches(input);
+        }
+    }
+
+    #[test]
+    fn test_garbage() {
+        // Splice together plenty of near misses.
+        let mut rng = StdRng::seed_from_u64(3);
+        let fragments = [
+            "mul(", "mul", "mu", "m", "mul(1,2)", "mul(12,", "mul(mul(", "34)", "5,6)", "1234,", "do()", "don't()",
+            "do", "don't", "don'", "(", ")", ",", "1", "23", "456", "x", "\n",
+        ];
+        let mut muls = 0;
+        for _ in 0..2000 {
+            let input = (0..24)
+                .map(|_| *fragments.choose(&mut rng).unwrap())
+                .collect::<String>();
+            muls += assert_same_matches(&input)
+                .iter()
+                .filter(|(_, m)| matches!(m, Instruction::Mul(..)))
+                .count();
+        }
+        assert!(muls > 2000, "only {muls} multiplications");
+    }
+
+    #[test]
+    fn test_trace() {
+        let steps = trace("xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))");
+        let lines = steps.iter().map(|step| step.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            [
+                "       1  mul(2,4)          on              8             8",
+                "      20  don't()           on              8             8",
+                "      28  mul(5,5)          off            33             8",
+                "      48  mul(11,8)         off           121             8",
+                "      59  do()              off           121             8",
+                "      64  mul(8,5)          on            161            48",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_register() {
+        let mut set = InstructionSet::default();
+        set.register(Spec {
+            name: "add",
+            arity: 2,
+            execute: |machine, args| machine.accumulate(u64::from(args[0] + args[1])),
+        });
+        set.register(Spec {
+            name: "reset",
+            arity: 0,
+            execute: |machine, _| *machine = Machine::default(),
+        });
+
+        let input = "mul(2,3)add(4,5)don't()add(1,add(10,20))reset()mul(1,1)adder(1,2)";
+        let mut machine = Machine::default();
+        let mut steps = Vec::new();
+        machine
+            .run(Scanner::new(input.as_bytes(), &set), |step| steps.push(*step))
+            .unwrap();
+        let found = steps
+            .iter()
+            .map(|step| (step.offset, step.instruction.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            [
+                (0, "mul(2,3)".to_owned()),
+                (8, "add(4,5)".to_owned()),
+                (16, "don't()".to_owned()),
+                (29, "add(10,20)".to_owned()),
+                (40, "reset()".to_owned()),
+                (47, "mul(1,1)".to_owned()),
+            ]
+        );
+        assert_eq!((steps[3].total, steps[3].enabled_total), (45, 15));
+        assert_eq!(
+            machine,
+            Machine {
+                enabled: true,
+                total: 1,
+                enabled_total: 1
+            }
+        );
+
+        // The puzzle's instructions are unaffected.
+        assert_eq!(steps[0].instruction, Instruction::Mul(2, 3));
+        assert!(matches!(
+            steps[1].instruction,
+            Instruction::Other(Spec { name: "add", .. }, [4, 5, 0, 0])
+        ));
+    }
 }
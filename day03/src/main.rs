@@ -1,5 +1,27 @@
-fn main() {
-    let (part1, part2) = day03::solve();
-    println!("{part1}");
-    println!("{part2}");
+use std::{env, process::ExitCode};
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [] => {
+            let (part1, part2) = day03::solve();
+            println!("{part1}");
+            println!("{part2}");
+        }
+        ["--trace"] => {
+            let input = include_str!(concat!(env!("OUT_DIR"), "/input.txt"));
+            println!(
+                "{:>8}  {:<16}  {:<3}  {:>12}  {:>12}",
+                "offset", "instruction", "on?", "total", "enabled"
+            );
+            for step in day03::trace(input) {
+                println!("{step}");
+            }
+        }
+        _ => {
+            eprintln!("usage: day03 [--trace]");
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
 }
@@ -3,67 +3,391 @@ use std::fmt::Display;
 use grid::Grid;
 use rayon::prelude::*;
 
-const MAS: &[u8] = b"MAS";
-const DIRECTIONS: [(isize, isize); 8] = [(0, -1), (0, 1), (-1, 0), (1, 0), (1, 1), (-1, 1), (-1, -1), (1, -1)];
+/// The directions a word can be read in, as `(dy, dx)`.
+pub const DIRECTIONS: [(isize, isize); 8] = [(0, -1), (0, 1), (-1, 0), (1, 0), (1, 1), (-1, 1), (-1, -1), (1, -1)];
 
-#[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    let input = include_str!("input.txt");
-    let grid = Grid::from_vec(
-        input.bytes().filter(|&b| b != b'\n').collect(),
-        input.lines().next().unwrap().len(),
-    );
+/// The X-shaped pattern of part 2, which may be turned any way.
+const X_MAS: &str = "M.S\n.A.\nM.S";
 
-    rayon::join(|| solve_part1(&grid), || solve_part2(&grid))
+fn parse() -> Grid<u8> {
+    parse_input(include_str!(concat!(env!("OUT_DIR"), "/input.txt")))
 }
 
-fn solve_part1(grid: &Grid<u8>) -> usize {
-    DIRECTIONS
+pub fn parse_input(input: &str) -> Grid<u8> {
+    aoc_trace::span!(
+        "parse",
+        Grid::from_vec(
+            input.bytes().filter(|&b| b != b'\n').collect(),
+            input.lines().next().unwrap().len(),
+        )
+    )
+}
+
+/// A word found in the grid, read from `start` one step of `direction` at a time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Occurrence {
+    pub start: (usize, usize),
+    pub direction: (isize, isize),
+}
+
+impl Occurrence {
+    /// The cells of the first `len` letters.
+    pub fn cells(&self, len: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..len as isize).map(|i| {
+            (
+                self.start.0.wrapping_add_signed(i * self.direction.0),
+                self.start.1.wrapping_add_signed(i * self.direction.1),
+            )
+        })
+    }
+}
+
+/// Every occurrence of `word` in any of the eight directions, sorted by start then direction.
+///
+/// A word that reads the same in two directions, like a palindrome or a single letter, is found once per direction.
+pub fn find_word(grid: &Grid<u8>, word: &[u8]) -> Vec<Occurrence> {
+    let Some((&first, rest)) = word.split_first() else {
+        return Vec::new();
+    };
+    let mut found = DIRECTIONS
         .into_par_iter()
-        .map(|dir| {
+        .flat_map_iter(|direction| {
             grid.indexed_iter()
-                .filter(|(_, &c)| c == b'X')
-                .filter(|&((y, x), _)| {
-                    (0..MAS.len())
+                .filter(move |(_, &c)| c == first)
+                .filter(move |&((y, x), _)| {
+                    (0..rest.len())
                         .scan((y, x), |(y, x), _| {
-                            *y = y.checked_add_signed(dir.0)?;
-                            *x = x.checked_add_signed(dir.1)?;
+                            *y = y.checked_add_signed(direction.0)?;
+                            *x = x.checked_add_signed(direction.1)?;
                             grid.get(*y, *x)
                         })
-                        .eq(MAS)
+                        .eq(rest)
                 })
-                .count()
+                .map(move |(start, _)| Occurrence { start, direction })
         })
-        .sum()
+        .collect::<Vec<_>>();
+    found.sort_unstable();
+    found
 }
 
-fn solve_part2(grid: &Grid<u8>) -> usize {
-    grid.indexed_iter()
-        .filter(|(_, &c)| c == b'A')
-        .filter(|((y, x), _)| {
-            let first_diagonal = [
-                (y.checked_sub(1), x.checked_sub(1)),
-                (y.checked_add(1), x.checked_add(1)),
-            ];
-            let second_diagonal = [
-                (y.checked_sub(1), x.checked_add(1)),
-                (y.checked_add(1), x.checked_sub(1)),
-            ];
-
-            let do_count = |corners: [(Option<usize>, Option<usize>); 2]| {
-                let mut m = 0u8;
-                let mut s = 0u8;
-                for (y, x) in corners {
-                    match y.zip(x).and_then(|(y, x)| grid.get(y, x)) {
-                        Some(b'M') => m += 1,
-                        Some(b'S') => s += 1,
-                        _ => {}
-                    }
+/// A 2D pattern of letters, where `.` matches anything.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Stencil {
+    /// The letters to match as `(y, x, letter)`, sorted.
+    cells: Vec<(usize, usize, u8)>,
+    height: usize,
+    width: usize,
+}
+
+impl Stencil {
+    /// A stencil from rows of letters separated by newlines.
+    pub fn new(pattern: &str) -> Self {
+        let mut cells = Vec::new();
+        let mut height = 0;
+        let mut width = 0;
+        for (y, row) in pattern.lines().enumerate() {
+            height = y + 1;
+            width = width.max(row.len());
+            cells.extend(
+                row.bytes()
+                    .enumerate()
+                    .filter(|&(_, c)| c != b'.')
+                    .map(|(x, c)| (y, x, c)),
+            );
+        }
+        cells.sort_unstable();
+        Self { cells, height, width }
+    }
+
+    pub fn cells(&self) -> &[(usize, usize, u8)] {
+        &self.cells
+    }
+
+    fn map(&self, height: usize, width: usize, f: impl Fn(usize, usize) -> (usize, usize)) -> Self {
+        let mut cells = self
+            .cells
+            .iter()
+            .map(|&(y, x, c)| {
+                let (y, x) = f(y, x);
+                (y, x, c)
+            })
+            .collect::<Vec<_>>();
+        cells.sort_unstable();
+        Self { cells, height, width }
+    }
+
+    /// A quarter turn clockwise.
+    fn rotate(&self) -> Self {
+        self.map(self.width, self.height, |y, x| (x, self.height - 1 - y))
+    }
+
+    /// Left to right.
+    fn reflect(&self) -> Self {
+        self.map(self.height, self.width, |y, x| (y, self.width - 1 - x))
+    }
+
+    /// The distinct ways the stencil can be turned and flipped, starting with itself.
+    pub fn orientations(&self) -> Vec<Self> {
+        let mut orientations: Vec<Self> = Vec::new();
+        for mut stencil in [self.clone(), self.reflect()] {
+            for _ in 0..4 {
+                let next = stencil.rotate();
+                if !orientations.contains(&stencil) {
+                    orientations.push(stencil);
                 }
-                (m, s)
-            };
+                stencil = next;
+            }
+        }
+        orientations
+    }
+
+    fn matches_at(&self, grid: &Grid<u8>, (y, x): (usize, usize)) -> bool {
+        self.cells
+            .iter()
+            .all(|&(dy, dx, c)| grid.get(y + dy, x + dx) == Some(&c))
+    }
+}
+
+/// Where a stencil matched, in one of its orientations.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Placement {
+    /// The top left corner of the oriented stencil.
+    pub origin: (usize, usize),
+    /// Index of the orientation in [`Stencil::orientations`].
+    pub orientation: usize,
+    /// The letters that matched, as `(y, x)`.
+    pub cells: Vec<(usize, usize)>,
+}
 
-            do_count(first_diagonal) == (1, 1) && do_count(second_diagonal) == (1, 1)
+/// Every placement of the stencil in any of its orientations, sorted.
+pub fn find_stencil(grid: &Grid<u8>, stencil: &Stencil) -> Vec<Placement> {
+    let mut found = stencil
+        .orientations()
+        .into_par_iter()
+        .enumerate()
+        .flat_map_iter(|(orientation, stencil)| {
+            let ys = 0..(grid.rows() + 1).saturating_sub(stencil.height);
+            let xs = 0..(grid.cols() + 1).saturating_sub(stencil.width);
+            ys.flat_map(move |y| xs.clone().map(move |x| (y, x)))
+                .filter(|&origin| stencil.matches_at(grid, origin))
+                .map(|(y, x)| Placement {
+                    origin: (y, x),
+                    orientation,
+                    cells: stencil.cells.iter().map(|&(dy, dx, _)| (y + dy, x + dx)).collect(),
+                })
+                .collect::<Vec<_>>()
         })
-        .count()
+        .collect::<Vec<_>>();
+    found.sort_unstable();
+    found
+}
+
+#[inline]
+pub fn solve() -> (impl Display, impl Display) {
+    let grid = parse();
+    rayon::join(|| solve_part1(&grid), || solve_part2(&grid))
+}
+
+pub fn part1() -> impl Display {
+    solve_part1(&parse())
+}
+
+pub fn part2() -> impl Display {
+    solve_part2(&parse())
+}
+
+/// How to show the letters of the matches.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Style {
+    /// The letters as they are, like the puzzle text does.
+    Plain,
+    /// The letters coloured by direction or orientation.
+    Ansi,
+}
+
+/// Foreground colours, one per direction.
+const COLOURS: [u8; 8] = [31, 32, 33, 34, 35, 36, 91, 94];
+
+/// The grid with the given cells kept and everything else replaced by `.`; a cell in several matches gets the colour of
+/// the last one.
+fn render(grid: &Grid<u8>, cells: impl IntoIterator<Item = ((usize, usize), usize)>, style: Style) -> String {
+    let mut colours = Grid::new(grid.rows(), grid.cols());
+    for ((y, x), colour) in cells {
+        colours[(y, x)] = Some(COLOURS[colour % COLOURS.len()]);
+    }
+
+    let mut out = String::new();
+    for (row, colours) in grid.iter_rows().zip(colours.iter_rows()) {
+        for (&c, colour) in row.zip(colours) {
+            match (colour, style) {
+                (None, _) => out.push('.'),
+                (Some(_), Style::Plain) => out.push(char::from(c)),
+                (Some(colour), Style::Ansi) => out += &format!("\x1b[{colour}m{}\x1b[0m", char::from(c)),
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// The grid showing only the letters of the occurrences of a word that is `len` letters long.
+pub fn render_words(grid: &Grid<u8>, len: usize, occurrences: &[Occurrence], style: Style) -> String {
+    let cells = occurrences.iter().flat_map(|occurrence| {
+        let colour = DIRECTIONS.iter().position(|&d| d == occurrence.direction).unwrap();
+        occurrence.cells(len).map(move |cell| (cell, colour))
+    });
+    render(grid, cells, style)
+}
+
+/// The grid showing only the letters of the placements of a stencil.
+pub fn render_stencils(grid: &Grid<u8>, placements: &[Placement], style: Style) -> String {
+    let cells = placements
+        .iter()
+        .flat_map(|placement| placement.cells.iter().map(|&cell| (cell, placement.orientation)));
+    render(grid, cells, style)
+}
+
+/// The grid showing the matches of either part.
+pub fn render_part(grid: &Grid<u8>, part: u8, style: Style) -> String {
+    match part {
+        1 => render_words(grid, 4, &find_word(grid, b"XMAS"), style),
+        2 => render_stencils(grid, &find_stencil(grid, &Stencil::new(X_MAS)), style),
+        _ => panic!("there is no part {part}"),
+    }
+}
+
+pub fn render_input(part: u8, style: Style) -> String {
+    render_part(&parse(), part, style)
+}
+
+fn solve_part1(grid: &Grid<u8>) -> usize {
+    aoc_trace::span!("part1");
+    find_word(grid, b"XMAS").len()
+}
+
+fn solve_part2(grid: &Grid<u8>) -> usize {
+    aoc_trace::span!("part2");
+    find_stencil(grid, &Stencil::new(X_MAS)).len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+MMMSXXMASM
+MSAMXMSMSA
+AMXSXMAAMM
+MSAMASMSMX
+XMASAMXAMM
+XXAMMXXAMA
+SMSMSASXSS
+SAXAMASAAA
+MAMMMXMMMM
+MXMXAXMASX
+";
+
+    #[test]
+    fn test_example() {
+        let grid = parse_input(EXAMPLE);
+        assert_eq!(solve_part1(&grid), 18);
+        assert_eq!(solve_part2(&grid), 9);
+    }
+
+    #[test]
+    fn test_find_word() {
+        let grid = parse_input("XMAS\nMM..\nA.A.\nS..S\n");
+        assert_eq!(
+            find_word(&grid, b"XMAS"),
+            [
+                Occurrence {
+                    start: (0, 0),
+                    direction: (0, 1)
+                },
+                Occurrence {
+                    start: (0, 0),
+                    direction: (1, 0)
+                },
+                Occurrence {
+                    start: (0, 0),
+                    direction: (1, 1)
+                },
+            ]
+        );
+        assert_eq!(find_word(&grid, b"SAMX").len(), 3);
+        assert_eq!(
+            find_word(&grid, b"MA")[0].cells(2).collect::<Vec<_>>(),
+            [(0, 1), (0, 2)]
+        );
+        assert!(find_word(&grid, b"").is_empty());
+    }
+
+    #[test]
+    fn test_stencils() {
+        assert_eq!(Stencil::new(X_MAS).orientations().len(), 4);
+        let plus = Stencil::new(".M.\nMAS\n.S.");
+        assert_eq!(plus.orientations().len(), 4);
+        assert_eq!(Stencil::new(".X.\nXXX\n.X.").orientations().len(), 1);
+        assert_eq!(Stencil::new("XM\n.A\n.S").orientations().len(), 8);
+
+        let grid = parse_input("..S...\n.SAM..\n..M...\n....M.\n...MAS\n....S.\n");
+        let found = find_stencil(&grid, &plus);
+        assert_eq!(
+            found,
+            [
+                Placement {
+                    origin: (0, 1),
+                    orientation: 2,
+                    cells: vec![(0, 2), (1, 1), (1, 2), (1, 3), (2, 2)],
+                },
+                Placement {
+                    origin: (3, 3),
+                    orientation: 0,
+                    cells: vec![(3, 4), (4, 3), (4, 4), (4, 5), (5, 4)],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let grid = parse_input(EXAMPLE);
+        assert_eq!(
+            render_part(&grid, 1, Style::Plain),
+            "\
+....XXMAS.
+.SAMXMS...
+...S..A...
+..A.A.MS.X
+XMASAMX.MM
+X.....XA.A
+S.S.S.S.SS
+.A.A.A.A.A
+..M.M.M.MM
+.X.X.XMASX
+"
+        );
+        assert_eq!(
+            render_part(&grid, 2, Style::Plain),
+            "\
+.M.S......
+..A..MSMS.
+.M.S.MAA..
+..A.ASMSM.
+.M.S.M....
+..........
+S.S.S.S.S.
+.A.A.A.A..
+M.M.M.M.M.
+..........
+"
+        );
+
+        let small = parse_input("XMAS.\n.....\nSAMX.\n");
+        assert_eq!(
+            render_part(&small, 1, Style::Ansi),
+            "\x1b[32mX\x1b[0m\x1b[32mM\x1b[0m\x1b[32mA\x1b[0m\x1b[32mS\x1b[0m.\n.....\n\
+             \x1b[31mS\x1b[0m\x1b[31mA\x1b[0m\x1b[31mM\x1b[0m\x1b[31mX\x1b[0m.\n"
+        );
+    }
 }
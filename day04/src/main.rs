@@ -1,5 +1,28 @@
-fn main() {
-    let (part1, part2) = day04::solve();
-    println!("{part1}");
-    println!("{part2}");
+use std::{env, process::ExitCode};
+
+use day04::Style;
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let (part, style) = match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [] => {
+            let (part1, part2) = day04::solve();
+            println!("{part1}");
+            println!("{part2}");
+            return ExitCode::SUCCESS;
+        }
+        ["--render", part] => (part, Style::Plain),
+        ["--render", part, "--ansi"] => (part, Style::Ansi),
+        _ => ("", Style::Plain),
+    };
+    match part {
+        "1" | "2" => {
+            print!("{}", day04::render_input(part.parse().unwrap(), style));
+            ExitCode::SUCCESS
+        }
+        _ => {
+            eprintln!("usage: day04 [--render 1|2 [--ansi]]");
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -1,66 +1,482 @@
-use std::{cmp::Ordering, fmt::Display};
+use std::{cmp::Ordering, fmt, fmt::Display};
 
 use rayon::prelude::*;
 
-struct Order {
-    map: [u128; 100],
+/// Pages a bitmask has room for.
+const BITS: usize = u128::BITS as usize;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// There is no blank line between the rules and the updates.
+    MissingUpdates,
+    /// The line isn't a rule like `47|53`.
+    Rule { line: usize },
+    /// The line isn't an update like `75,47,61`.
+    Update { line: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::MissingUpdates => write!(f, "no blank line between the rules and the updates"),
+            ParseError::Rule { line } => write!(f, "line {line}: expected a rule like `47|53`"),
+            ParseError::Update { line } => write!(f, "line {line}: expected pages like `75,47,61`"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Which pages must come after each page, by index.
+enum Successors {
+    Bits(Vec<u128>),
+    /// Sorted.
+    Lists(Vec<Vec<u32>>),
+}
+
+/// The ordering rules.
+///
+/// When every page number is below 128, they index the bitmasks directly. Otherwise pages are indexed by their rank
+/// among those named in the rules, which still fits in bitmasks as long as there are at most 128 of them.
+pub struct Order {
+    /// The pages named in the rules, sorted, or `None` if pages are their own index.
+    pages: Option<Vec<u32>>,
+    successors: Successors,
 }
 
 impl Order {
-    fn new() -> Self {
-        Self { map: [0; 100] }
+    pub fn new(rules: &[(u32, u32)]) -> Self {
+        let max = rules.iter().map(|&(before, after)| before.max(after)).max();
+        let pages = match max {
+            Some(max) if max as usize >= BITS => {
+                let mut pages = rules
+                    .iter()
+                    .flat_map(|&(before, after)| [before, after])
+                    .collect::<Vec<_>>();
+                pages.sort_unstable();
+                pages.dedup();
+                Some(pages)
+            }
+            _ => None,
+        };
+
+        let mut order = Self {
+            pages,
+            successors: Successors::Lists(Vec::new()),
+        };
+        let len = order.pages.as_ref().map_or(BITS, Vec::len);
+        let edges = rules
+            .iter()
+            .map(|&(before, after)| (order.index(before).unwrap(), order.index(after).unwrap()));
+
+        order.successors = if len <= BITS {
+            let mut bits = vec![0; len];
+            for (before, after) in edges {
+                bits[before] |= 1 << after;
+            }
+            Successors::Bits(bits)
+        } else {
+            let mut lists = vec![Vec::new(); len];
+            for (before, after) in edges {
+                lists[before].push(after as u32);
+            }
+            for list in &mut lists {
+                list.sort_unstable();
+                list.dedup();
+            }
+            Successors::Lists(lists)
+        };
+        order
+    }
+
+    /// Index of a page, or `None` if no rule names it.
+    fn index(&self, page: u32) -> Option<usize> {
+        match &self.pages {
+            None => ((page as usize) < BITS).then_some(page as usize),
+            Some(pages) => pages.binary_search(&page).ok(),
+        }
+    }
+
+    /// Whether a rule says `before` comes before `after`.
+    pub fn contains(&self, before: u32, after: u32) -> bool {
+        let (Some(before), Some(after)) = (self.index(before), self.index(after)) else {
+            return false;
+        };
+        match &self.successors {
+            Successors::Bits(bits) => bits[before] & (1 << after) != 0,
+            Successors::Lists(lists) => lists[before].binary_search(&(after as u32)).is_ok(),
+        }
     }
+}
+
+/// The rules and the updates, each of which is a list of pages.
+pub fn parse(input: &str) -> Result<(Order, Vec<Vec<u32>>), ParseError> {
+    let (rules_s, updates_s) = input.split_once("\n\n").ok_or(ParseError::MissingUpdates)?;
+
+    let rules = aoc_trace::span!(
+        "parse_rules",
+        rules_s
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                let (before, after) = line.split_once('|').ok_or(ParseError::Rule { line: i + 1 })?;
+                before
+                    .parse()
+                    .ok()
+                    .zip(after.parse().ok())
+                    .ok_or(ParseError::Rule { line: i + 1 })
+            })
+            .collect::<Result<Vec<_>, _>>()?
+    );
+    let order = Order::new(&rules);
+
+    let first_update = rules.len() + 2;
+    let updates = aoc_trace::span!(
+        "parse_updates",
+        updates_s
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.is_empty())
+            .map(|(i, line)| {
+                line.split(',')
+                    .map(|page| page.parse().map_err(|_| ParseError::Update { line: first_update + i }))
+                    .collect()
+            })
+            .collect::<Result<Vec<_>, _>>()?
+    );
+
+    Ok((order, updates))
+}
+
+/// What the rules say about an update.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub update: Vec<u32>,
+    /// Whether no rule puts a page after one that comes later in the update.
+    pub correct: bool,
+    /// Pages of the update each of which must come before the next, and the last before the first.
+    pub cycle: Option<Vec<u32>>,
+    /// Pairs of pages of the update that no rule orders either way, in the order they appear in.
+    pub incomparable: Vec<(u32, u32)>,
+    /// The pages in an order that follows the rules, for an incorrect update without cycles; one of several if there
+    /// are incomparable pages.
+    pub corrected: Option<Vec<u32>>,
+}
 
-    fn add(&mut self, before: u8, after: u8) {
-        debug_assert!(before < 100);
-        debug_assert!(after < 100);
-        self.map[before as usize] |= 1 << after;
+impl Report {
+    /// Whether the rules order the update's pages in exactly one way, as solving the puzzle assumes.
+    pub fn is_consistent(&self) -> bool {
+        self.cycle.is_none() && self.incomparable.is_empty()
     }
+}
 
-    fn contains(&self, before: u8, after: u8) -> bool {
-        debug_assert!(before < 100);
-        debug_assert!(after < 100);
-        self.map[before as usize] & (1 << after) != 0
+fn join(pages: &[u32], separator: &str) -> String {
+    pages.iter().map(u32::to_string).collect::<Vec<_>>().join(separator)
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", join(&self.update, ","))?;
+        if let Some(corrected) = &self.corrected {
+            write!(f, " -> {}", join(corrected, ","))?;
+        } else if self.correct {
+            write!(f, " (correct)")?;
+        }
+        if let Some(cycle) = &self.cycle {
+            write!(f, "; cycle {} -> {}", join(cycle, " -> "), cycle[0])?;
+        }
+        if !self.incomparable.is_empty() {
+            let pairs = self
+                .incomparable
+                .iter()
+                .map(|(a, b)| format!("{a}/{b}"))
+                .collect::<Vec<_>>();
+            write!(f, "; incomparable {}", pairs.join(", "))?;
+        }
+        Ok(())
     }
 }
 
+/// Check an update against the rules between its pages.
+pub fn check(order: &Order, update: &[u32]) -> Report {
+    let n = update.len();
+    let before = |i: usize, j: usize| order.contains(update[i], update[j]);
+
+    let correct = (0..n).all(|j| (0..j).all(|i| !before(j, i)));
+    let mut incomparable = Vec::new();
+    for j in 0..n {
+        for i in 0..j {
+            if !before(i, j) && !before(j, i) {
+                incomparable.push((update[i], update[j]));
+            }
+        }
+    }
+
+    // Kahn's algorithm, taking the earliest page in the update whenever there is a choice.
+    let mut in_degree = (0..n)
+        .map(|j| (0..n).filter(|&i| before(i, j)).count())
+        .collect::<Vec<_>>();
+    let mut done = vec![false; n];
+    let mut sorted = Vec::with_capacity(n);
+    while let Some(i) = (0..n).find(|&i| !done[i] && in_degree[i] == 0) {
+        done[i] = true;
+        sorted.push(update[i]);
+        for (j, degree) in in_degree.iter_mut().enumerate() {
+            if before(i, j) {
+                *degree -= 1;
+            }
+        }
+    }
+
+    // Every page left has a predecessor that is also left, so walking back from one must come around in a cycle.
+    let cycle = (sorted.len() < n).then(|| {
+        let mut seen = vec![None; n];
+        let mut path = Vec::new();
+        let mut page = (0..n).find(|&i| !done[i]).unwrap();
+        while seen[page].is_none() {
+            seen[page] = Some(path.len());
+            path.push(update[page]);
+            page = (0..n).find(|&i| !done[i] && before(i, page)).unwrap();
+        }
+        let mut cycle = path.split_off(seen[page].unwrap());
+        cycle.reverse();
+        cycle
+    });
+
+    let corrected = (!correct && cycle.is_none()).then_some(sorted);
+    Report {
+        update: update.to_vec(),
+        correct,
+        cycle,
+        incomparable,
+        corrected,
+    }
+}
+
+/// Check every update.
+pub fn check_input(input: &str) -> Result<Vec<Report>, ParseError> {
+    let (order, updates) = parse(input)?;
+    Ok(updates.par_iter().map(|update| check(&order, update)).collect())
+}
+
 #[inline]
 pub fn solve() -> (impl Display, impl Display) {
-    let input = include_str!("input.txt");
-    let (order_s, updates) = input.split_once("\n\n").unwrap();
-
-    let mut order = Order::new();
-    order_s
-        .lines()
-        .map(|line| {
-            let (before, after) = line.split_once('|').unwrap();
-            (before.parse::<u8>().unwrap(), after.parse::<u8>().unwrap())
-        })
-        .for_each(|(before, after)| order.add(before, after));
+    solve_input(include_str!(concat!(env!("OUT_DIR"), "/input.txt"))).unwrap_or_else(|e| panic!("{e}"))
+}
 
-    let updates = updates
-        .par_lines()
-        .map(|update| update.split(',').map(|x| x.parse::<u8>().unwrap()).collect::<Vec<_>>());
+pub fn solve_input(input: &str) -> Result<(u64, u64), ParseError> {
+    let (order, updates) = parse(input)?;
 
+    aoc_trace::span!("check_updates");
+    Ok(midpoints(&order, updates, true))
+}
+
+pub fn part1() -> impl Display {
+    let (order, updates) =
+        parse(include_str!(concat!(env!("OUT_DIR"), "/input.txt"))).unwrap_or_else(|e| panic!("{e}"));
+    midpoints(&order, updates, false).0
+}
+
+pub fn part2() -> impl Display {
+    let (order, updates) =
+        parse(include_str!(concat!(env!("OUT_DIR"), "/input.txt"))).unwrap_or_else(|e| panic!("{e}"));
+    midpoints(&order, updates, true).1
+}
+
+/// The sums of the middle pages of the updates that are in order, and, if `sort` is set, of those that aren't once
+/// they are.
+fn midpoints(order: &Order, updates: Vec<Vec<u32>>, sort: bool) -> (u64, u64) {
     updates
+        .into_par_iter()
         .map(|mut update| {
             let midpoint = update.len() / 2;
             if update.is_sorted_by(|&n, &m| order.contains(n, m)) {
-                (update[midpoint] as u16, 0)
+                (u64::from(update[midpoint]), 0)
+            } else if !sort {
+                (0, 0)
             } else {
                 (
                     0,
-                    *update
-                        .select_nth_unstable_by(midpoint, |&n, &m| {
-                            if order.contains(n, m) {
-                                Ordering::Less
-                            } else {
-                                Ordering::Greater
-                            }
-                        })
-                        .1 as u16,
+                    u64::from(
+                        *update
+                            .select_nth_unstable_by(midpoint, |&n, &m| {
+                                if order.contains(n, m) {
+                                    Ordering::Less
+                                } else {
+                                    Ordering::Greater
+                                }
+                            })
+                            .1,
+                    ),
                 )
             }
         })
         .reduce(|| (0, 0), |(a, b), (c, d)| (a + c, b + d))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+47|53
+97|13
+97|61
+97|47
+75|29
+61|13
+75|53
+29|13
+97|29
+53|29
+61|53
+97|53
+61|29
+47|13
+75|47
+97|75
+47|61
+75|61
+47|29
+75|13
+53|13
+
+75,47,61,53,29
+97,61,53,29,13
+75,29,13
+75,97,47,61,53
+61,13,29
+97,13,75,29,47
+";
+
+    /// The example with every page number replaced.
+    fn renumbered(page: impl Fn(u32) -> u32) -> String {
+        let mut out = String::new();
+        let mut number = String::new();
+        for c in EXAMPLE.chars() {
+            if c.is_ascii_digit() {
+                number.push(c);
+                continue;
+            }
+            if !number.is_empty() {
+                out += &page(number.parse().unwrap()).to_string();
+                number.clear();
+            }
+            out.push(c);
+        }
+        out
+    }
+
+    #[test]
+    fn test_example() {
+        assert_eq!(solve_input(EXAMPLE), Ok((143, 123)));
+    }
+
+    #[test]
+    fn test_large_pages() {
+        // Past the bitmask's range, but still few enough pages to use bitmasks.
+        let (order, _) = parse(&renumbered(|page| page * 1000)).unwrap();
+        assert!(matches!(order.successors, Successors::Bits(_)));
+        assert_eq!(solve_input(&renumbered(|page| page * 1000)), Ok((143_000, 123_000)));
+        assert_eq!(
+            solve_input(&renumbered(|page| u32::MAX - page)),
+            Ok((3 * u64::from(u32::MAX) - 143, 3 * u64::from(u32::MAX) - 123))
+        );
+    }
+
+    #[test]
+    fn test_many_pages() {
+        // Pages 1000..1300 ordered by their number, as a chain of rules, with updates in both orders.
+        let pages = 1000..1300u32;
+        let mut input = String::new();
+        for page in pages.clone().skip(1) {
+            input += &format!("{}|{page}\n", page - 1);
+        }
+        input.push('\n');
+        let (mut p1, mut p2) = (0, 0);
+        for start in pages.clone().step_by(7).take(30) {
+            let update = (start..start + 2).collect::<Vec<_>>();
+            let joined = update.iter().map(u32::to_string).collect::<Vec<_>>().join(",");
+            if start % 2 == 0 {
+                input += &format!("{joined}\n");
+                p1 += u64::from(update[1]);
+            } else {
+                let reversed = update.iter().rev().map(u32::to_string).collect::<Vec<_>>().join(",");
+                input += &format!("{reversed}\n");
+                p2 += u64::from(update[1]);
+            }
+        }
+
+        let (order, _) = parse(&input).unwrap();
+        assert!(matches!(order.successors, Successors::Lists(_)));
+        assert!(order.contains(1000, 1001) && !order.contains(1001, 1000) && !order.contains(1000, 5));
+        assert_eq!(solve_input(&input), Ok((p1, p2)));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(solve_input("47|53\n"), Err(ParseError::MissingUpdates));
+        assert_eq!(
+            solve_input("47|53\n47-53\n\n47,53\n"),
+            Err(ParseError::Rule { line: 2 })
+        );
+        assert_eq!(
+            solve_input("47|53\n4294967296|53\n\n47,53\n"),
+            Err(ParseError::Rule { line: 2 })
+        );
+        assert_eq!(
+            solve_input("47|53\n\n47,53\n47,,53\n"),
+            Err(ParseError::Update { line: 4 })
+        );
+        assert_eq!(
+            ParseError::Update { line: 4 }.to_string(),
+            "line 4: expected pages like `75,47,61`"
+        );
+    }
+
+    #[test]
+    fn test_check() {
+        let reports = check_input(EXAMPLE).unwrap();
+        assert!(reports.iter().all(Report::is_consistent));
+        let corrected = reports
+            .iter()
+            .map(|report| report.corrected.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            corrected,
+            [
+                None,
+                None,
+                None,
+                Some(vec![97, 75, 47, 61, 53]),
+                Some(vec![61, 29, 13]),
+                Some(vec![97, 75, 47, 29, 13]),
+            ]
+        );
+        assert_eq!(reports[3].to_string(), "75,97,47,61,53 -> 97,75,47,61,53");
+        assert_eq!(reports[0].to_string(), "75,47,61,53,29 (correct)");
+
+        let (order, _) = parse("1|2\n2|3\n3|1\n3|4\n\n1\n").unwrap();
+        let report = check(&order, &[4, 1, 2, 3]);
+        assert_eq!(report.cycle, Some(vec![1, 2, 3]));
+        assert_eq!(report.corrected, None);
+        assert!(!report.correct);
+        assert_eq!(report.incomparable, [(4, 1), (4, 2)]);
+        assert_eq!(
+            report.to_string(),
+            "4,1,2,3; cycle 1 -> 2 -> 3 -> 1; incomparable 4/1, 4/2"
+        );
+
+        let report = check(&order, &[2, 4, 1]);
+        assert_eq!(report.cycle, None);
+        assert_eq!(report.corrected, Some(vec![4, 1, 2]));
+        assert_eq!(report.incomparable, [(2, 4), (4, 1)]);
+        assert!(!report.is_consistent());
+
+        // Nothing to correct when no rule applies.
+        let report = check(&order, &[2, 4]);
+        assert!(report.correct);
+        assert_eq!(report.corrected, None);
+        assert_eq!(report.to_string(), "2,4 (correct); incomparable 2/4");
+    }
+}
@@ -1,5 +1,35 @@
-fn main() {
-    let (part1, part2) = day05::solve();
-    println!("{part1}");
-    println!("{part2}");
+use std::{env, process::ExitCode};
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [] => {
+            let (part1, part2) = day05::solve();
+            println!("{part1}");
+            println!("{part2}");
+        }
+        ["--check"] => {
+            let input = include_str!(concat!(env!("OUT_DIR"), "/input.txt"));
+            let reports = match day05::check_input(input) {
+                Ok(reports) => reports,
+                Err(e) => {
+                    eprintln!("{e}");
+                    return ExitCode::FAILURE;
+                }
+            };
+            for (i, report) in reports.iter().enumerate() {
+                println!("{:>4}: {report}", i + 1);
+            }
+            let inconsistent = reports.iter().filter(|report| !report.is_consistent()).count();
+            println!(
+                "{inconsistent} of {} updates aren't totally ordered by the rules",
+                reports.len()
+            );
+        }
+        _ => {
+            eprintln!("usage: day05 [--check]");
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
 }
@@ -1,97 +1,664 @@
 use fixedbitset::FixedBitSet;
+use grid::Grid;
 use rayon::prelude::*;
-use std::fmt::Display;
+use std::fmt::{self, Display};
 
 const DIRECTIONS: [(i8, i8); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
-const SIDE: u8 = 130;
 
-fn pos2idx(y: u8, x: u8) -> usize {
-    usize::from(y) * usize::from(SIDE) + usize::from(x)
+/// Which way the guard is going, in the order it turns in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
 }
 
-#[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    let input = include_str!("input.txt");
-    let map = grid::Grid::from_vec(
-        input.bytes().filter(|&b| b != b'\n').collect(),
-        input.lines().next().unwrap().len(),
-    );
-    debug_assert_eq!(map.rows(), map.cols());
-
-    // Find the starting position.
-    let (start_pos, _) = map.indexed_iter().find(|&(_, &c)| c == b'^').unwrap();
-    let start_pos = (start_pos.0 as u8, start_pos.1 as u8);
-
-    // Convert the map into a bitset of walls for faster lookup.
-    let mut walls = FixedBitSet::with_capacity(usize::from(SIDE) * usize::from(SIDE));
-    for (pos, &c) in map.indexed_iter() {
-        if c == b'#' {
-            walls.insert(pos2idx(pos.0 as u8, pos.1 as u8));
-        }
-    }
-
-    // Walk the walk for part 1.
-    let p1_visited_by_dir = do_solve(&walls, None, start_pos).0;
-
-    // We've got a bitset for each direction, but we need to combine them into one for part one.
-    let (first, rest) = p1_visited_by_dir.split_first().unwrap();
-    let mut p1_visited = first.clone();
-    for visited in rest {
-        p1_visited |= visited;
-    }
-    let p1 = p1_visited.count_ones(..);
-
-    // Let's move on to part 2.
-    // We'll utilize the visited map for part one as candidates for obstacles; since the problem text specifies the
-    // start is not an option, we'll just remove it from consideration.
-    p1_visited.remove(pos2idx(start_pos.0, start_pos.1));
-    let p2 = (0..SIDE)
-        .into_par_iter()
-        .flat_map(|y| (0..SIDE).into_par_iter().map(move |x| (y, x)))
-        .filter(|&(y, x)| p1_visited.contains(pos2idx(y, x)))
-        .filter(|&(y, x)| {
-            let (_, enters_loop) = do_solve(&walls, Some((y, x)), start_pos);
-            enters_loop
-        })
-        .count();
-
-    (p1, p2)
-}
-
-fn do_solve(
-    walls: &FixedBitSet,
-    extra_wall: Option<(u8, u8)>,
-    (mut y, mut x): (u8, u8),
-) -> ([FixedBitSet; DIRECTIONS.len()], bool) {
-    let mut visited_by_dir: [FixedBitSet; DIRECTIONS.len()] =
-        std::array::from_fn(|_| FixedBitSet::with_capacity(usize::from(SIDE) * usize::from(SIDE)));
-
-    loop {
-        for ((dy, dx), visited) in DIRECTIONS.into_iter().zip(&mut visited_by_dir) {
-            loop {
-                let idx = pos2idx(y, x);
-                if visited.contains(idx) {
-                    return (visited_by_dir, true);
+impl Direction {
+    const ALL: [Self; 4] = [Self::Up, Self::Right, Self::Down, Self::Left];
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let c = match self {
+            Direction::Up => '^',
+            Direction::Right => '>',
+            Direction::Down => 'v',
+            Direction::Left => '<',
+        };
+        write!(f, "{c}")
+    }
+}
+
+/// A cell and which way the guard goes there.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Step {
+    pub pos: (usize, usize),
+    pub direction: Direction,
+}
+
+/// Where an obstruction makes the guard loop, and the loop, as the turning points it goes around with the direction
+/// the guard is going in when it reaches each one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Obstruction {
+    pub pos: (usize, usize),
+    pub cycle: Vec<Step>,
+}
+
+/// Everything the guard does.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Patrol {
+    /// The cells the guard visits, in the order it first gets to them, with which way it's going then.
+    pub path: Vec<Step>,
+    /// The places where an obstruction makes the guard loop, sorted.
+    pub obstructions: Vec<Obstruction>,
+}
+
+/// A coordinate type, as small as the map allows to keep the jump table compact.
+///
+/// Moving up from row 0 or left from column 0 wraps around to the largest value, which is out of bounds as long as the
+/// map's sides are smaller than it; this allows us to avoid checking for negative values.
+trait Coord: Copy + Eq + Send + Sync {
+    const MAX: usize;
+
+    fn from_usize(n: usize) -> Self;
+    fn index(self) -> usize;
+    fn step(self, d: i8) -> Self;
+}
+
+macro_rules! impl_coord {
+    ($($t:ty),*) => {$(
+        impl Coord for $t {
+            const MAX: usize = <$t>::MAX as usize;
+
+            fn from_usize(n: usize) -> Self {
+                n as $t
+            }
+
+            fn index(self) -> usize {
+                self as usize
+            }
+
+            fn step(self, d: i8) -> Self {
+                self.wrapping_add_signed(d.into())
+            }
+        }
+    )*};
+}
+
+impl_coord!(u8, u32);
+
+/// The turning points of a walk, as cell and direction, in a bitset that is cleared by removing just what was inserted.
+struct Turns {
+    seen: FixedBitSet,
+    inserted: Vec<usize>,
+    /// The turning point that was reached twice.
+    repeated: Option<usize>,
+}
+
+impl Turns {
+    fn new(cells: usize) -> Self {
+        Self {
+            seen: FixedBitSet::with_capacity(cells * DIRECTIONS.len()),
+            inserted: Vec::new(),
+            repeated: None,
+        }
+    }
+
+    /// Record a turning point, returning whether it's new.
+    fn insert(&mut self, idx: usize, dir: usize) -> bool {
+        let bit = idx * DIRECTIONS.len() + dir;
+        if self.seen.put(bit) {
+            self.repeated = Some(bit);
+            return false;
+        }
+        self.inserted.push(bit);
+        true
+    }
+
+    fn clear(&mut self) {
+        for bit in self.inserted.drain(..) {
+            self.seen.set(bit, false);
+        }
+        self.repeated = None;
+    }
+
+    /// The turning points of the loop, as cell and direction, starting with the one that was reached twice.
+    fn looped(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let start = self.repeated.map_or(self.inserted.len(), |repeated| {
+            self.inserted.iter().position(|&bit| bit == repeated).unwrap()
+        });
+        self.inserted[start..]
+            .iter()
+            .map(|&bit| (bit / DIRECTIONS.len(), bit % DIRECTIONS.len()))
+    }
+}
+
+struct Lab<C> {
+    height: C,
+    width: C,
+    /// For each cell and direction, the coordinate along the direction's axis of the first wall ahead, or of the first
+    /// cell off the map.
+    jumps: Vec<[C; DIRECTIONS.len()]>,
+    start: (C, C),
+}
+
+impl<C: Coord> Lab<C> {
+    fn new(map: &Grid<u8>) -> Self {
+        debug_assert!(map.rows().max(map.cols()) < C::MAX);
+        let (height, width) = (map.rows(), map.cols());
+
+        // Find the starting position.
+        let (start, _) = map.indexed_iter().find(|&(_, &c)| c == b'^').unwrap();
+
+        // Sweep each row and column both ways, remembering the last wall seen.
+        let off_map = C::from_usize(C::MAX);
+        let mut jumps = vec![[off_map; DIRECTIONS.len()]; height * width];
+        for x in 0..width {
+            let mut wall = off_map;
+            for y in 0..height {
+                jumps[y * width + x][0] = wall;
+                if map[(y, x)] == b'#' {
+                    wall = C::from_usize(y);
                 }
-                visited.insert(idx);
-
-                // Move into the new direction, checking if we go out of bounds.
-                // Since SIDE is small enough, overflow'll never happen and underflow is beneficial (it allows us to
-                // avoid checking for negative values).
-                let new_y = y.wrapping_add_signed(dy);
-                let new_x = x.wrapping_add_signed(dx);
-                if new_y >= SIDE || new_x >= SIDE {
-                    return (visited_by_dir, false);
+            }
+            let mut wall = C::from_usize(height);
+            for y in (0..height).rev() {
+                jumps[y * width + x][2] = wall;
+                if map[(y, x)] == b'#' {
+                    wall = C::from_usize(y);
                 }
+            }
+        }
+        for y in 0..height {
+            let mut wall = off_map;
+            for x in 0..width {
+                jumps[y * width + x][3] = wall;
+                if map[(y, x)] == b'#' {
+                    wall = C::from_usize(x);
+                }
+            }
+            let mut wall = C::from_usize(width);
+            for x in (0..width).rev() {
+                jumps[y * width + x][1] = wall;
+                if map[(y, x)] == b'#' {
+                    wall = C::from_usize(x);
+                }
+            }
+        }
+
+        Self {
+            height: C::from_usize(height),
+            width: C::from_usize(width),
+            jumps,
+            start: (C::from_usize(start.0), C::from_usize(start.1)),
+        }
+    }
+
+    fn cells(&self) -> usize {
+        self.height.index() * self.width.index()
+    }
+
+    fn pos2idx(&self, y: C, x: C) -> usize {
+        y.index() * self.width.index() + x.index()
+    }
+
+    fn solve(&self) -> (usize, usize) {
+        // Walk the walk for part 1.
+        let path = aoc_trace::span!("part1", self.path());
+
+        // Let's move on to part 2.
+        aoc_trace::span!("part2");
+        (path.len(), self.count_loops(&path))
+    }
+
+    /// How many cells of the path make the guard loop when obstructed.
+    fn count_loops(&self, path: &[((C, C), usize)]) -> usize {
+        // The cells of the path are the only places where an obstacle changes anything, and since the guard doesn't
+        // get to one before first visiting it, the walk can start right in front of it; the problem text specifies the
+        // start is not an option, so it's skipped.
+        path[1..]
+            .par_iter()
+            .map_init(|| Turns::new(self.cells()), |turns, &step| self.loops(turns, step))
+            .filter(|&enters_loop| enters_loop)
+            .count()
+    }
+
+    /// Whether an obstruction at a cell of the path makes the guard loop, given the direction it first got there in.
+    fn loops(&self, turns: &mut Turns, ((y, x), dir): ((C, C), usize)) -> bool {
+        let (dy, dx) = DIRECTIONS[dir];
+        turns.clear();
+        self.walk(Some((y, x)), (y.step(-dy), x.step(-dx)), dir, turns, |_, _, _| {})
+    }
+
+    fn step(&self, ((y, x), dir): ((C, C), usize)) -> Step {
+        Step {
+            pos: (y.index(), x.index()),
+            direction: Direction::ALL[dir],
+        }
+    }
+
+    fn patrol(&self) -> Patrol {
+        let path = self.path();
+        let obstructions = path[1..]
+            .par_iter()
+            .map_init(
+                || Turns::new(self.cells()),
+                |turns, &step| {
+                    self.loops(turns, step).then(|| Obstruction {
+                        pos: self.step(step).pos,
+                        cycle: turns
+                            .looped()
+                            .map(|(idx, dir)| Step {
+                                pos: (idx / self.width.index(), idx % self.width.index()),
+                                direction: Direction::ALL[dir],
+                            })
+                            .collect(),
+                    })
+                },
+            )
+            .flatten()
+            .collect::<Vec<_>>();
+
+        let mut patrol = Patrol {
+            path: path.into_iter().map(|step| self.step(step)).collect(),
+            obstructions,
+        };
+        patrol.obstructions.sort_unstable_by_key(|obstruction| obstruction.pos);
+        patrol
+    }
+
+    /// The map with the guard's path drawn on it, like the puzzle's illustrations: `|` where it goes up or down, `-`
+    /// where it goes left or right and `+` where it does both, with the obstruction, if any, as `O`.
+    fn render(&self, map: &Grid<u8>, obstruction: Option<(C, C)>) -> String {
+        const VERTICAL: u8 = 1;
+        const HORIZONTAL: u8 = 2;
 
-                let new_idx = pos2idx(new_y, new_x);
-                if Some((new_y, new_x)) == extra_wall || walls.contains(new_idx) {
+        let mut moves = Grid::new(map.rows(), map.cols());
+        let mut turns = Turns::new(self.cells());
+        self.walk(obstruction, self.start, 0, &mut turns, |(mut y, mut x), end, dir| {
+            let (dy, dx) = DIRECTIONS[dir];
+            let axis = if dy != 0 { VERTICAL } else { HORIZONTAL };
+            loop {
+                moves[(y.index(), x.index())] |= axis;
+                if (y, x) == end {
                     break;
                 }
+                (y, x) = (y.step(dy), x.step(dx));
+            }
+        });
+
+        let mut out = String::new();
+        for (y, row) in moves.iter_rows().enumerate() {
+            for (x, &axes) in row.enumerate() {
+                out.push(match axes {
+                    _ if Some((C::from_usize(y), C::from_usize(x))) == obstruction => 'O',
+                    _ if map[(y, x)] == b'^' => '^',
+                    VERTICAL => '|',
+                    HORIZONTAL => '-',
+                    0 => char::from(map[(y, x)]),
+                    _ => '+',
+                });
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// The cells the guard visits, in the order it first gets to them, with the direction it's going in then.
+    fn path(&self) -> Vec<((C, C), usize)> {
+        let mut visited = FixedBitSet::with_capacity(self.cells());
+        let mut path = Vec::new();
+        let mut visit = |(y, x): (C, C), dir| {
+            if !visited.put(self.pos2idx(y, x)) {
+                path.push(((y, x), dir));
+            }
+        };
+        visit(self.start, 0);
+
+        let mut turns = Turns::new(self.cells());
+        let enters_loop = self.walk(None, self.start, 0, &mut turns, |(mut y, mut x), end, dir| {
+            let (dy, dx) = DIRECTIONS[dir];
+            while (y, x) != end {
+                (y, x) = (y.step(dy), x.step(dx));
+                visit((y, x), dir);
+            }
+        });
+        assert!(!enters_loop, "the guard never leaves");
+        path
+    }
+
+    /// Walk from wall to wall, calling `segment` with the start, end and direction of each straight line walked, and
+    /// return whether the guard enters a loop.
+    fn walk(
+        &self,
+        extra_wall: Option<(C, C)>,
+        (mut y, mut x): (C, C),
+        mut dir: usize,
+        turns: &mut Turns,
+        mut segment: impl FnMut((C, C), (C, C), usize),
+    ) -> bool {
+        loop {
+            let mut wall = self.jumps[self.pos2idx(y, x)][dir];
+
+            // Patch in the extra wall if it's between the guard and the next one; an off-map wall above or to the
+            // left wraps around, so it's one past it that must be compared.
+            if let Some((wy, wx)) = extra_wall {
+                let (wy, wx) = (wy.index(), wx.index());
+                match dir {
+                    0 if wx == x.index() && wall.step(1).index() <= wy && wy < y.index() => wall = C::from_usize(wy),
+                    1 if wy == y.index() && x.index() < wx && wx < wall.index() => wall = C::from_usize(wx),
+                    2 if wx == x.index() && y.index() < wy && wy < wall.index() => wall = C::from_usize(wy),
+                    3 if wy == y.index() && wall.step(1).index() <= wx && wx < x.index() => wall = C::from_usize(wx),
+                    _ => {}
+                }
+            }
+
+            // Stop in front of the wall, or at the edge of the map if there is none.
+            let (dy, dx) = DIRECTIONS[dir];
+            let (end, leaves) = if dy != 0 {
+                ((wall.step(-dy), x), wall.index() >= self.height.index())
+            } else {
+                ((y, wall.step(-dx)), wall.index() >= self.width.index())
+            };
+            segment((y, x), end, dir);
+            if leaves {
+                return false;
+            }
+
+            (y, x) = end;
+            if !turns.insert(self.pos2idx(y, x), dir) {
+                return true;
+            }
+            dir = (dir + 1) % DIRECTIONS.len();
+        }
+    }
+}
+
+/// Run `$body` with `$lab` bound to the lab of the map, using the smallest coordinate type that fits it.
+macro_rules! with_lab {
+    ($map:expr, |$lab:ident| $body:expr) => {
+        if $map.rows().max($map.cols()) < u8::MAX.index() {
+            let $lab = Lab::<u8>::new(&$map);
+            $body
+        } else {
+            let $lab = Lab::<u32>::new(&$map);
+            $body
+        }
+    };
+}
+
+fn parse(input: &str) -> Grid<u8> {
+    aoc_trace::span!(
+        "parse",
+        Grid::from_vec(
+            input.bytes().filter(|&b| b != b'\n').collect(),
+            input.lines().next().unwrap().len(),
+        )
+    )
+}
+
+#[inline]
+pub fn solve() -> (impl Display, impl Display) {
+    solve_input(include_str!(concat!(env!("OUT_DIR"), "/input.txt")))
+}
+
+pub fn solve_input(input: &str) -> (usize, usize) {
+    let map = parse(input);
+    with_lab!(map, |lab| lab.solve())
+}
+
+pub fn part1() -> impl Display {
+    let map = parse(include_str!(concat!(env!("OUT_DIR"), "/input.txt")));
+    with_lab!(map, |lab| lab.path().len())
+}
+
+pub fn part2() -> impl Display {
+    let map = parse(include_str!(concat!(env!("OUT_DIR"), "/input.txt")));
+    with_lab!(map, |lab| lab.count_loops(&lab.path()))
+}
 
-                y = new_y;
-                x = new_x;
+pub fn patrol(input: &str) -> Patrol {
+    let map = parse(input);
+    with_lab!(map, |lab| lab.patrol())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ObstructionError {
+    /// The position is off the map.
+    OffMap { pos: (usize, usize) },
+    /// There's already an obstruction there.
+    Wall { pos: (usize, usize) },
+    /// The guard is standing there.
+    Guard { pos: (usize, usize) },
+}
+
+impl fmt::Display for ObstructionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObstructionError::OffMap { pos: (y, x) } => write!(f, "{y},{x} is off the map"),
+            ObstructionError::Wall { pos: (y, x) } => write!(f, "{y},{x} is already obstructed"),
+            ObstructionError::Guard { pos: (y, x) } => write!(f, "{y},{x} is where the guard starts"),
+        }
+    }
+}
+
+impl std::error::Error for ObstructionError {}
+
+/// The map with the guard's path drawn on it, with an obstruction at `(y, x)` if given, which must be on an empty cell.
+pub fn render(input: &str, obstruction: Option<(usize, usize)>) -> Result<String, ObstructionError> {
+    let map = parse(input);
+    if let Some(pos) = obstruction {
+        match map.get(pos.0, pos.1) {
+            None => return Err(ObstructionError::OffMap { pos }),
+            Some(b'#') => return Err(ObstructionError::Wall { pos }),
+            Some(b'^') => return Err(ObstructionError::Guard { pos }),
+            Some(_) => {}
+        }
+    }
+    Ok(with_lab!(map, |lab| lab.render(
+        &map,
+        obstruction.map(|(y, x)| (Coord::from_usize(y), Coord::from_usize(x)))
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::iter;
+
+    use rand::prelude::*;
+
+    use super::*;
+
+    const EXAMPLE: &str = "\
+....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...
+";
+
+    /// Walk the guard one cell at a time, returning the cells visited or `None` if it loops.
+    fn naive_walk(map: &Grid<u8>, extra_wall: Option<(usize, usize)>) -> Option<Vec<bool>> {
+        let ((mut y, mut x), _) = map.indexed_iter().find(|&(_, &c)| c == b'^').unwrap();
+        let mut dir = 0;
+        let mut seen = vec![false; map.rows() * map.cols() * 4];
+        let mut visited = vec![false; map.rows() * map.cols()];
+        loop {
+            let state = (y * map.cols() + x) * 4 + dir;
+            if seen[state] {
+                return None;
+            }
+            seen[state] = true;
+            visited[y * map.cols() + x] = true;
+
+            let (dy, dx) = DIRECTIONS[dir];
+            let (Some(ny), Some(nx)) = (y.checked_add_signed(dy.into()), x.checked_add_signed(dx.into())) else {
+                return Some(visited);
+            };
+            match map.get(ny, nx) {
+                None => return Some(visited),
+                Some(b'#') => dir = (dir + 1) % 4,
+                Some(_) if Some((ny, nx)) == extra_wall => dir = (dir + 1) % 4,
+                Some(_) => (y, x) = (ny, nx),
             }
         }
     }
+
+    fn naive_solve(input: &str) -> (usize, usize) {
+        let map = parse(input);
+        let visited = naive_walk(&map, None).unwrap();
+        let p1 = visited.iter().filter(|&&v| v).count();
+        let p2 = map
+            .indexed_iter()
+            .filter(|&((y, x), &c)| c == b'.' && visited[y * map.cols() + x])
+            .filter(|&(pos, _)| naive_walk(&map, Some(pos)).is_none())
+            .count();
+        (p1, p2)
+    }
+
+    /// A random map with walls on about one cell in `density`, and the guard somewhere in the middle.
+    fn random_map(rng: &mut StdRng, height: usize, width: usize, density: u32) -> String {
+        let mut map = String::new();
+        for y in 0..height {
+            for x in 0..width {
+                map.push(if (y, x) == (height / 2, width / 2) {
+                    '^'
+                } else if rng.gen_ratio(1, density) {
+                    '#'
+                } else {
+                    '.'
+                });
+            }
+            map.push('\n');
+        }
+        map
+    }
+
+    #[test]
+    fn test_example() {
+        assert_eq!(solve_input(EXAMPLE), (41, 6));
+        assert_eq!(naive_solve(EXAMPLE), (41, 6));
+    }
+
+    #[test]
+    fn test_rectangular() {
+        for (height, width, density) in [(7, 23, 6), (40, 13, 9), (60, 300, 12), (300, 40, 10)] {
+            // Like the real inputs, the guard must leave the map.
+            let mut rng = StdRng::seed_from_u64(height as u64 * width as u64);
+            let input = iter::repeat_with(|| random_map(&mut rng, height, width, density))
+                .find(|input| naive_walk(&parse(input), None).is_some())
+                .unwrap();
+            assert_eq!(solve_input(&input), naive_solve(&input), "{height}x{width}");
+        }
+    }
+
+    #[test]
+    fn test_patrol() {
+        let patrol = patrol(EXAMPLE);
+        assert_eq!(patrol.path.len(), 41);
+        let step = |pos, direction| Step { pos, direction };
+        assert_eq!(
+            patrol.path[4..7],
+            [
+                step((2, 4), Direction::Up),
+                step((1, 4), Direction::Up),
+                step((1, 5), Direction::Right),
+            ]
+        );
+        assert_eq!(patrol.path.last(), Some(&step((9, 7), Direction::Down)));
+
+        let positions = patrol.obstructions.iter().map(|o| o.pos).collect::<Vec<_>>();
+        assert_eq!(positions, [(6, 3), (7, 6), (7, 7), (8, 1), (8, 3), (9, 7)]);
+        let mut cycle = patrol.obstructions[0].cycle.clone();
+        cycle.sort_unstable();
+        assert_eq!(
+            cycle,
+            [
+                step((1, 4), Direction::Up),
+                step((1, 8), Direction::Right),
+                step((6, 4), Direction::Left),
+                step((6, 8), Direction::Down),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            render(EXAMPLE, Some((6, 3))).unwrap(),
+            "\
+....#.....
+....+---+#
+....|...|.
+..#.|...|.
+....|..#|.
+....|...|.
+.#.O^---+.
+........#.
+#.........
+......#...
+"
+        );
+        assert_eq!(
+            render(EXAMPLE, Some((7, 6))).unwrap(),
+            "\
+....#.....
+....+---+#
+....|...|.
+..#.|...|.
+..+-+-+#|.
+..|.|.|.|.
+.#+-^-+-+.
+......O.#.
+#.........
+......#...
+"
+        );
+        assert_eq!(
+            render(EXAMPLE, None).unwrap(),
+            "\
+....#.....
+....+---+#
+....|...|.
+..#.|...|.
+..+-+-+#|.
+..|.|.|.|.
+.#+-^-+-+.
+.+----++#.
+#+----+|..
+......#|..
+"
+        );
+    }
+
+    #[test]
+    fn test_render_errors() {
+        // 256 would wrap around to 0 as a u8.
+        assert_eq!(
+            render(EXAMPLE, Some((256, 0))),
+            Err(ObstructionError::OffMap { pos: (256, 0) })
+        );
+        assert_eq!(
+            render(EXAMPLE, Some((0, 10))),
+            Err(ObstructionError::OffMap { pos: (0, 10) })
+        );
+        assert_eq!(
+            render(EXAMPLE, Some((0, 4))),
+            Err(ObstructionError::Wall { pos: (0, 4) })
+        );
+        assert_eq!(
+            render(EXAMPLE, Some((6, 4))),
+            Err(ObstructionError::Guard { pos: (6, 4) })
+        );
+        assert_eq!(
+            ObstructionError::OffMap { pos: (0, 10) }.to_string(),
+            "0,10 is off the map"
+        );
+    }
 }
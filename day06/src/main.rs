@@ -1,5 +1,50 @@
-fn main() {
-    let (part1, part2) = day06::solve();
-    println!("{part1}");
-    println!("{part2}");
+use std::{env, process::ExitCode};
+
+fn main() -> ExitCode {
+    let input = include_str!(concat!(env!("OUT_DIR"), "/input.txt"));
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [] => {
+            let (part1, part2) = day06::solve();
+            println!("{part1}");
+            println!("{part2}");
+        }
+        ["--path"] => {
+            for step in day06::patrol(input).path {
+                println!("{},{} {}", step.pos.0, step.pos.1, step.direction);
+            }
+        }
+        ["--loops"] => {
+            for obstruction in day06::patrol(input).obstructions {
+                let cycle = obstruction
+                    .cycle
+                    .iter()
+                    .map(|step| format!("{},{} {}", step.pos.0, step.pos.1, step.direction))
+                    .collect::<Vec<_>>();
+                println!("{},{}: {}", obstruction.pos.0, obstruction.pos.1, cycle.join(" -> "));
+            }
+        }
+        ["--render"] => print!("{}", day06::render(input, None).unwrap()),
+        ["--render", pos] => {
+            let Some((y, x)) = pos
+                .split_once(',')
+                .and_then(|(y, x)| y.parse().ok().zip(x.parse().ok()))
+            else {
+                eprintln!("expected the obstruction as Y,X");
+                return ExitCode::FAILURE;
+            };
+            match day06::render(input, Some((y, x))) {
+                Ok(map) => print!("{map}"),
+                Err(e) => {
+                    eprintln!("{e}");
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+        _ => {
+            eprintln!("usage: day06 [--path | --loops | --render [Y,X]]");
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
 }
@@ -1,42 +1,592 @@
-use std::fmt::Display;
+use std::fmt::{self, Display};
 
 use rayon::prelude::*;
 
 #[inline]
 pub fn solve() -> (impl Display, impl Display) {
-    let input = include_str!("input.txt");
+    let input = include_str!(concat!(env!("OUT_DIR"), "/input.txt"));
 
-    rayon::join(|| do_solve::<false>(input), || do_solve::<true>(input))
+    let (part1, part2) = rayon::join(
+        || aoc_trace::span!("part1", total(input, PART1)),
+        || aoc_trace::span!("part2", total(input, PART2)),
+    );
+    (
+        part1.unwrap_or_else(|e| panic!("{e}")),
+        part2.unwrap_or_else(|e| panic!("{e}")),
+    )
 }
 
-fn ends_with(a: u64, b: u64) -> bool {
-    b <= a && (a - b) % 10u64.pow(b.ilog10() + 1) == 0
+pub fn part1() -> impl Display {
+    total(include_str!(concat!(env!("OUT_DIR"), "/input.txt")), PART1).unwrap_or_else(|e| panic!("{e}"))
+}
+
+pub fn part2() -> impl Display {
+    total(include_str!(concat!(env!("OUT_DIR"), "/input.txt")), PART2).unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// A binary operator, evaluated left to right, along with its inverse for searching backwards from the target.
+pub trait Operator: fmt::Debug + Sync {
+    fn symbol(&self) -> &'static str;
+
+    /// `a op b`, or `None` if it doesn't fit in a `u128`.
+    fn apply(&self, a: u128, b: u128) -> Option<u128>;
+
+    /// The `a` for which `a op b == target`.
+    fn invert(&self, target: u128, b: u128) -> Inverse;
+}
+
+/// What the left operand of an operator can be for it to give some target.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Inverse {
+    /// Nothing that fits in a `u128`.
+    Impossible,
+    /// This and nothing else.
+    Unique(u128),
+    /// Anything at all, like the `a` in `a * 0 == 0`.
+    Any,
+}
+
+impl From<Option<u128>> for Inverse {
+    fn from(a: Option<u128>) -> Self {
+        a.map_or(Inverse::Impossible, Inverse::Unique)
+    }
+}
+
+#[derive(Debug)]
+pub struct Add;
+
+impl Operator for Add {
+    fn symbol(&self) -> &'static str {
+        "+"
+    }
+
+    fn apply(&self, a: u128, b: u128) -> Option<u128> {
+        a.checked_add(b)
+    }
+
+    fn invert(&self, target: u128, b: u128) -> Inverse {
+        target.checked_sub(b).into()
+    }
+}
+
+#[derive(Debug)]
+pub struct Mul;
+
+impl Operator for Mul {
+    fn symbol(&self) -> &'static str {
+        "*"
+    }
+
+    fn apply(&self, a: u128, b: u128) -> Option<u128> {
+        a.checked_mul(b)
+    }
+
+    fn invert(&self, target: u128, b: u128) -> Inverse {
+        match div_rem(target, b) {
+            Some((q, r)) => (r == 0).then_some(q).into(),
+            None if target == 0 => Inverse::Any,
+            None => Inverse::Impossible,
+        }
+    }
+}
+
+/// `a / b` and `a % b`, or `None` if `b` is zero.
+///
+/// Dividing `u128`s takes a library call, so when the numbers fit in a `u64`, as they usually do, they're divided as
+/// such.
+fn div_rem(a: u128, b: u128) -> Option<(u128, u128)> {
+    if b == 0 {
+        return None;
+    }
+    Some(match (u64::try_from(a), u64::try_from(b)) {
+        (Ok(a), Ok(b)) => (u128::from(a / b), u128::from(a % b)),
+        _ => (a / b, a % b),
+    })
+}
+
+/// The digits of `b` after those of `a`.
+#[derive(Debug)]
+pub struct Concat;
+
+/// The powers of ten that fit in a `u128`.
+const POWERS_OF_TEN: [u128; 39] = {
+    let mut powers = [1; 39];
+    let mut i = 1;
+    while i < powers.len() {
+        powers[i] = powers[i - 1] * 10;
+        i += 1;
+    }
+    powers
+};
+
+/// The power of ten with as many zeros as `b` has digits, if it fits.
+fn shift(b: u128) -> Option<u128> {
+    let digits = match u64::try_from(b) {
+        Ok(b) => b.checked_ilog10(),
+        Err(_) => b.checked_ilog10(),
+    };
+    POWERS_OF_TEN.get(digits.unwrap_or(0) as usize + 1).copied()
+}
+
+impl Operator for Concat {
+    fn symbol(&self) -> &'static str {
+        "||"
+    }
+
+    fn apply(&self, a: u128, b: u128) -> Option<u128> {
+        a.checked_mul(shift(b)?)?.checked_add(b)
+    }
+
+    fn invert(&self, target: u128, b: u128) -> Inverse {
+        let Some((q, r)) = target
+            .checked_sub(b)
+            .zip(shift(b))
+            .and_then(|(a, shift)| div_rem(a, shift))
+        else {
+            return Inverse::Impossible;
+        };
+        (r == 0).then_some(q).into()
+    }
+}
+
+#[derive(Debug)]
+pub struct Sub;
+
+impl Operator for Sub {
+    fn symbol(&self) -> &'static str {
+        "-"
+    }
+
+    fn apply(&self, a: u128, b: u128) -> Option<u128> {
+        a.checked_sub(b)
+    }
+
+    fn invert(&self, target: u128, b: u128) -> Inverse {
+        target.checked_add(b).into()
+    }
+}
+
+#[derive(Debug)]
+pub struct Xor;
+
+impl Operator for Xor {
+    fn symbol(&self) -> &'static str {
+        "^"
+    }
+
+    fn apply(&self, a: u128, b: u128) -> Option<u128> {
+        Some(a ^ b)
+    }
+
+    fn invert(&self, target: u128, b: u128) -> Inverse {
+        Inverse::Unique(target ^ b)
+    }
+}
+
+/// The operators that may be used, in the order in which they're tried; the ones whose inverse exists the least often
+/// should come first.
+pub type Operators = [&'static dyn Operator];
+
+pub const PART1: &Operators = &[&Mul, &Add];
+pub const PART2: &Operators = &[&Mul, &Concat, &Add];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The line isn't an equation like `3267: 81 40 27`.
+    Malformed { line: usize },
+    /// A number on the line doesn't fit in a `u128`.
+    Overflow { line: usize },
+    /// The calibration result doesn't fit in a `u128`.
+    TotalOverflow,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Malformed { line } => write!(f, "line {line}: expected an equation like `3267: 81 40 27`"),
+            Error::Overflow { line } => write!(f, "line {line}: number too large for a u128"),
+            Error::TotalOverflow => write!(f, "the calibration result is too large for a u128"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Values with operators between them, evaluated left to right.
+#[derive(Clone, Debug)]
+pub struct Expression {
+    pub values: Vec<u128>,
+    /// One fewer than the values.
+    pub ops: Vec<&'static dyn Operator>,
+}
+
+impl Expression {
+    /// The value of the expression, or `None` if it overflows along the way.
+    pub fn evaluate(&self) -> Option<u128> {
+        self.ops
+            .iter()
+            .zip(&self.values[1..])
+            .try_fold(self.values[0], |acc, (op, &value)| op.apply(acc, value))
+    }
+}
+
+impl Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.values[0])?;
+        for (op, value) in self.ops.iter().zip(&self.values[1..]) {
+            write!(f, " {} {value}", op.symbol())?;
+        }
+        Ok(())
+    }
+}
+
+/// Each operator, with what the rest of an expression must equal for it to put `n` last in one equal to `target`.
+fn inverses(target: u128, n: u128, ops: &Operators) -> impl Iterator<Item = (&'static dyn Operator, Inverse)> + '_ {
+    ops.iter().map(move |&op| (op, op.invert(target, n)))
 }
 
 // Solution translated from:
 // https://www.reddit.com/r/adventofcode/comments/1h8l3z5/2024_day_7_solutions/m0tv6di/
-fn is_tractable<const PART2: bool>(target: u64, values: &[u64]) -> bool {
+fn is_tractable(target: u128, values: &[u128], ops: &Operators) -> bool {
     let (&n, head) = values.split_last().unwrap();
     if head.is_empty() {
         return n == target;
     }
 
-    let q = target / n;
-    let r = target % n;
+    inverses(target, n, ops).any(|(_, inverse)| match inverse {
+        Inverse::Impossible => false,
+        Inverse::Unique(target) => is_tractable(target, head, ops),
+        Inverse::Any => find_forward(head, ops, &mut vec![]),
+    })
+}
+
+/// Like `is_tractable`, but pushes the operators of the first assignment found onto `found`, left to right.
+fn find_ops(target: u128, values: &[u128], ops: &Operators, found: &mut Vec<&'static dyn Operator>) -> bool {
+    let (&n, head) = values.split_last().unwrap();
+    if head.is_empty() {
+        return n == target;
+    }
+
+    inverses(target, n, ops).any(|(op, inverse)| {
+        let ok = match inverse {
+            Inverse::Impossible => false,
+            Inverse::Unique(target) => find_ops(target, head, ops, found),
+            Inverse::Any => find_forward(head, ops, found),
+        };
+        if ok {
+            found.push(op);
+        }
+        ok
+    })
+}
+
+/// How many assignments of operators make the values equal to the target.
+fn count_ops(target: u128, values: &[u128], ops: &Operators) -> u64 {
+    let (&n, head) = values.split_last().unwrap();
+    if head.is_empty() {
+        return u64::from(n == target);
+    }
+
+    inverses(target, n, ops)
+        .map(|(_, inverse)| match inverse {
+            Inverse::Impossible => 0,
+            Inverse::Unique(target) => count_ops(target, head, ops),
+            Inverse::Any => count_forward(head[0], &head[1..], ops),
+        })
+        .sum()
+}
+
+/// When any value will do for the start of an expression, there's nothing to search backwards from, so its assignments
+/// are tried forwards instead; only those that overflow are ruled out.
+///
+/// Pushes the operators of the first assignment that doesn't onto `found`, left to right.
+fn find_forward(values: &[u128], ops: &Operators, found: &mut Vec<&'static dyn Operator>) -> bool {
+    fn go(acc: u128, values: &[u128], ops: &Operators, found: &mut Vec<&'static dyn Operator>) -> bool {
+        let Some((&n, tail)) = values.split_first() else {
+            return true;
+        };
+        ops.iter().any(|&op| {
+            found.push(op);
+            let ok = op.apply(acc, n).is_some_and(|acc| go(acc, tail, ops, found));
+            if !ok {
+                found.pop();
+            }
+            ok
+        })
+    }
+
+    go(values[0], &values[1..], ops, found)
+}
+
+/// Like `find_forward`, but counts the assignments that don't overflow, starting from `acc`.
+fn count_forward(acc: u128, values: &[u128], ops: &Operators) -> u64 {
+    let Some((&n, tail)) = values.split_first() else {
+        return 1;
+    };
+    ops.iter()
+        .filter_map(|op| op.apply(acc, n))
+        .map(|acc| count_forward(acc, tail, ops))
+        .sum()
+}
+
+/// The lines, in a form that can be numbered in parallel.
+fn lines(input: &str) -> rayon::vec::IntoIter<&str> {
+    input.lines().collect::<Vec<_>>().into_par_iter()
+}
+
+fn parse_line(i: usize, line: &str) -> Result<(u128, Vec<u128>), Error> {
+    let number = |s: &str| {
+        // Numbers that fit in a u64, as they usually do, are much quicker to parse as such.
+        let n = if s.len() <= 19 {
+            aoc_parse::uint::<u64>(s.as_bytes()).map(u128::from)
+        } else {
+            s.parse().ok()
+        };
+        match n {
+            Some(n) => Ok(n),
+            None if !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) => Err(Error::Overflow { line: i + 1 }),
+            None => Err(Error::Malformed { line: i + 1 }),
+        }
+    };
+    let (target, values) = line.split_once(": ").ok_or(Error::Malformed { line: i + 1 })?;
+    let target = number(target)?;
+    let values = values.split(' ').map(number).collect::<Result<Vec<_>, _>>()?;
+    Ok((target, values))
+}
+
+/// The sum of the targets of the equations that can be made true with the given operators.
+pub fn total(input: &str, ops: &Operators) -> Result<u128, Error> {
+    let targets = lines(input)
+        .enumerate()
+        .map(|(i, line)| {
+            let (target, values) = parse_line(i, line)?;
+            Ok(is_tractable(target, &values, ops).then_some(target))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    targets
+        .into_iter()
+        .flatten()
+        .try_fold(0u128, u128::checked_add)
+        .ok_or(Error::TotalOverflow)
+}
 
-    (r == 0 && is_tractable::<PART2>(q, head))
-        || (PART2 && ends_with(target, n) && is_tractable::<PART2>(target / (10u64.pow(n.ilog10() + 1)), head))
-        || (n <= target && is_tractable::<PART2>(target - n, head))
+/// An equation that can be made true.
+#[derive(Clone, Debug)]
+pub struct Calibration {
+    pub target: u128,
+    /// One of the ways to make it true.
+    pub expression: Expression,
+    /// How many ways there are to make it true, if they were counted.
+    pub assignments: Option<u64>,
 }
 
-fn do_solve<const PART2: bool>(input: &str) -> u64 {
-    input
-        .par_lines()
-        .filter_map(|line| {
-            let (target, values) = line.split_once(": ").unwrap();
-            let target = target.parse::<u64>().unwrap();
-            let values = values.split(' ').map(|v| v.parse::<u64>().unwrap()).collect::<Vec<_>>();
-            is_tractable::<PART2>(target, &values).then_some(target)
+impl Display for Calibration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.target, self.expression)?;
+        if let Some(assignments) = self.assignments {
+            let ways = if assignments == 1 { "way" } else { "ways" };
+            write!(f, " ({assignments} {ways})")?;
+        }
+        Ok(())
+    }
+}
+
+/// The equations that can be made true with the given operators, in order, with the number of ways to make them true
+/// if `count` is set.
+pub fn calibrate(input: &str, ops: &Operators, count: bool) -> Result<Vec<Calibration>, Error> {
+    let calibrations = lines(input)
+        .enumerate()
+        .map(|(i, line)| {
+            let (target, values) = parse_line(i, line)?;
+            let mut found = Vec::with_capacity(values.len() - 1);
+            Ok(find_ops(target, &values, ops, &mut found).then(|| Calibration {
+                target,
+                assignments: count.then(|| count_ops(target, &values, ops)),
+                expression: Expression { values, ops: found },
+            }))
         })
-        .sum::<u64>()
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(calibrations.into_iter().flatten().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+190: 10 19
+3267: 81 40 27
+83: 17 5
+156: 15 6
+7290: 6 8 6 15
+161011: 16 10 13
+192: 17 8 14
+21037: 9 7 18 13
+292: 11 6 16 20
+";
+
+    /// Evaluate an expression the way the puzzle does, left to right, without going through `Operator`, or `None` if it
+    /// overflows along the way.
+    fn evaluate(expression: &Expression) -> Option<u128> {
+        expression
+            .ops
+            .iter()
+            .zip(&expression.values[1..])
+            .try_fold(expression.values[0], |acc, (op, &value)| match op.symbol() {
+                "+" => acc.checked_add(value),
+                "*" => acc.checked_mul(value),
+                "||" => format!("{acc}{value}").parse().ok(),
+                "-" => acc.checked_sub(value),
+                "^" => Some(acc ^ value),
+                symbol => panic!("unknown operator {symbol}"),
+            })
+    }
+
+    /// Every assignment of operators, evaluated forwards.
+    fn brute_force_count(target: u128, values: &[u128], ops: &Operators) -> u64 {
+        let slots = values.len() - 1;
+        (0..ops.len().pow(slots as u32))
+            .filter(|&assignment| {
+                let mut i = assignment;
+                let expression = Expression {
+                    values: values.to_vec(),
+                    ops: (0..slots)
+                        .map(|_| {
+                            let op = ops[i % ops.len()];
+                            i /= ops.len();
+                            op
+                        })
+                        .collect(),
+                };
+                evaluate(&expression) == Some(target)
+            })
+            .count() as u64
+    }
+
+    /// Check that every calibration evaluates to its target and was counted right.
+    fn check(calibrations: &[Calibration], ops: &Operators) {
+        for calibration in calibrations {
+            assert_eq!(
+                evaluate(&calibration.expression),
+                Some(calibration.target),
+                "{calibration}"
+            );
+            assert_eq!(
+                calibration.assignments,
+                Some(brute_force_count(
+                    calibration.target,
+                    &calibration.expression.values,
+                    ops
+                )),
+                "{calibration}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_example() {
+        assert_eq!(total(EXAMPLE, PART1), Ok(3749));
+        assert_eq!(total(EXAMPLE, PART2), Ok(11387));
+    }
+
+    #[test]
+    fn test_calibrate() {
+        for (ops, expected) in [(PART1, 3749), (PART2, 11387)] {
+            let calibrations = calibrate(EXAMPLE, ops, true).unwrap();
+            assert_eq!(calibrations.iter().map(|c| c.target).sum::<u128>(), expected);
+            check(&calibrations, ops);
+        }
+
+        let calibrations = calibrate(EXAMPLE, PART2, true).unwrap();
+        let shown = calibrations.iter().map(Calibration::to_string).collect::<Vec<_>>();
+        assert_eq!(shown[0], "190: 10 * 19 (1 way)");
+        assert_eq!(shown[1], "3267: 81 + 40 * 27 (2 ways)");
+        assert_eq!(shown[2], "156: 15 || 6 (1 way)");
+        assert_eq!(calibrate(EXAMPLE, PART1, false).unwrap()[0].assignments, None);
+    }
+
+    #[test]
+    fn test_many_assignments() {
+        // Adding or multiplying by 1 makes no difference at the start, so both count.
+        let calibrations = calibrate("3: 1 1 1 2\n", PART1, true).unwrap();
+        assert_eq!(
+            calibrations[0].assignments,
+            Some(brute_force_count(3, &[1, 1, 1, 2], PART1))
+        );
+        assert_eq!(evaluate(&calibrations[0].expression), Some(3));
+    }
+
+    #[test]
+    fn test_zero_operands() {
+        let input = format!(
+            "0: 0 0\n0: 5 0\n0: 3 4 0\n12: 3 4 0 12\n7: 7 0 0\n0: {} 2 0\n",
+            u128::MAX
+        );
+        for ops in [PART1, PART2] {
+            let calibrations = calibrate(&input, ops, true).unwrap();
+            check(&calibrations, ops);
+            assert_eq!(
+                calibrations.iter().map(|c| c.target).collect::<Vec<_>>(),
+                [0, 0, 0, 12, 7]
+            );
+            assert_eq!(total(&input, ops), Ok(19));
+        }
+
+        // Both `0 + 0` and `0 * 0` work, but the prefix can't be anything when it overflows.
+        let calibrations = calibrate(&input, PART1, true).unwrap();
+        assert_eq!(calibrations[0].assignments, Some(2));
+        assert_eq!(calibrations[2].assignments, Some(2));
+        assert_eq!(Mul.invert(0, 0), Inverse::Any);
+        assert_eq!(Mul.invert(1, 0), Inverse::Impossible);
+        assert_eq!(Mul.invert(12, 4), Inverse::Unique(3));
+    }
+
+    #[test]
+    fn test_other_operators() {
+        let ops: &Operators = &[&Mul, &Concat, &Add, &Sub, &Xor];
+        let input = "4: 10 3 3\n6: 5 3\n105: 12 2 5\n0: 7 7 7 7\n";
+        let calibrations = calibrate(input, ops, true).unwrap();
+        check(&calibrations, ops);
+        let shown = calibrations
+            .iter()
+            .map(|c| c.expression.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(shown, ["10 - 3 - 3", "5 ^ 3", "12 - 2 || 5", "7 - 7 * 7 * 7"]);
+        assert_eq!(total(input, ops), Ok(115));
+    }
+
+    #[test]
+    fn test_wide_targets() {
+        // Both overflow a u64, the second also in the middle.
+        let big = u128::from(u64::MAX) * 1000;
+        let input = format!("{big}: {} 1000\n{}: {} 2 5\n", u64::MAX, u128::MAX, u128::MAX / 2);
+        let calibrations = calibrate(&input, PART2, true).unwrap();
+        check(&calibrations, PART2);
+        assert_eq!(calibrations[0].expression.to_string(), format!("{} * 1000", u64::MAX));
+        assert_eq!(calibrations.len(), 1);
+
+        let expression = Expression {
+            values: vec![u128::MAX / 2, 2, 5],
+            ops: vec![&Mul, &Add],
+        };
+        assert_eq!(expression.evaluate(), None);
+        assert_eq!(Concat.apply(u128::MAX / 10, 99), None);
+        assert_eq!(Concat.invert(u128::MAX, 5), Inverse::Unique(u128::MAX / 10));
+        assert_eq!(Concat.invert(u128::MAX, u128::MAX), Inverse::Impossible);
+
+        assert_eq!(total(&input, PART1), Ok(big));
+        assert_eq!(
+            total(&format!("{0}: {0}\n{0}: {0}\n", u128::MAX), PART1),
+            Err(Error::TotalOverflow)
+        );
+        assert_eq!(
+            total(&format!("1: 1\n{}0: 1 2\n", u128::MAX), PART1),
+            Err(Error::Overflow { line: 2 })
+        );
+        assert_eq!(total("1: 1\n2: 1 x\n", PART1), Err(Error::Malformed { line: 2 }));
+        assert_eq!(
+            Error::Overflow { line: 2 }.to_string(),
+            "line 2: number too large for a u128"
+        );
+    }
 }
@@ -1,5 +1,35 @@
-fn main() {
-    let (part1, part2) = day07::solve();
-    println!("{part1}");
-    println!("{part2}");
+use std::{env, process::ExitCode};
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let (part, count) = match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [] => {
+            let (part1, part2) = day07::solve();
+            println!("{part1}");
+            println!("{part2}");
+            return ExitCode::SUCCESS;
+        }
+        ["--expressions", part] => (part, false),
+        ["--expressions", part, "--count"] => (part, true),
+        _ => ("", false),
+    };
+    if !matches!(part, "1" | "2") {
+        eprintln!("usage: day07 [--expressions 1|2 [--count]]");
+        return ExitCode::FAILURE;
+    }
+
+    let input = include_str!(concat!(env!("OUT_DIR"), "/input.txt"));
+    let ops = if part == "2" { day07::PART2 } else { day07::PART1 };
+    match day07::calibrate(input, ops, count) {
+        Ok(calibrations) => {
+            for calibration in calibrations {
+                println!("{calibration}");
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
 }
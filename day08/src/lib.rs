@@ -1,51 +1,417 @@
-use std::fmt::Display;
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+};
 
-type Coord = i16;
+type Coord = i32;
+
+/// A set of cells, as a bitset with as many `u64`s per row as it takes to cover the map's width.
+struct Bitmap {
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+impl Bitmap {
+    fn new(height: usize, width: usize) -> Self {
+        let words_per_row = width.div_ceil(64);
+        Self {
+            words_per_row,
+            words: vec![0; height * words_per_row],
+        }
+    }
+
+    fn insert(&mut self, y: usize, x: usize) {
+        self.words[y * self.words_per_row + x / 64] |= 1 << (x % 64);
+    }
+
+    fn len(&self) -> u32 {
+        self.words.iter().map(|n| n.count_ones()).sum()
+    }
+}
+
+/// The map's size and its antennas, grouped by frequency in reading order.
+struct Map {
+    height: usize,
+    width: usize,
+    antennas_by_frequency: Vec<Vec<(Coord, Coord)>>,
+}
+
+impl Map {
+    /// Call `f(frequency, i, j, k, (y, x))` for every antinode on the map, found `k` steps from the `i`th antenna of a
+    /// frequency towards its `j`th; those with `k == 2` are part 1's, and all of them are part 2's.
+    fn for_each_antinode(&self, mut f: impl FnMut(u8, usize, usize, Coord, (usize, usize))) {
+        for (frequency, antennas) in self.antennas_by_frequency.iter().enumerate() {
+            for (i, (y1, x1)) in antennas.iter().enumerate() {
+                for (j, (y2, x2)) in antennas.iter().enumerate() {
+                    let dy = y2 - y1;
+                    let dx = x2 - x1;
+                    if (dy, dx) == (0, 0) {
+                        continue;
+                    }
+
+                    for k in 0.. {
+                        let y0 = y1 + k * dy;
+                        let x0 = x1 + k * dx;
+
+                        if (0..self.width as Coord).contains(&x0) && (0..self.height as Coord).contains(&y0) {
+                            f(frequency as u8, i, j, k, (y0 as usize, x0 as usize));
+                        } else {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn parse(input: &str) -> Map {
+    let width = input.lines().next().unwrap().len();
+    let height = input.lines().count();
+
+    let mut antennas_by_frequency = vec![vec![]; 256];
+    aoc_trace::span!(
+        "parse",
+        input.bytes().filter(|&b| b != b'\n').enumerate().for_each(|(i, b)| {
+            if b.is_ascii_alphanumeric() {
+                antennas_by_frequency[b as usize].push(((i / width) as Coord, (i % width) as Coord));
+            }
+        })
+    );
+
+    Map {
+        height,
+        width,
+        antennas_by_frequency,
+    }
+}
 
 #[inline]
 pub fn solve() -> (impl Display, impl Display) {
-    let input = include_str!("input.txt");
+    solve_input(include_str!(concat!(env!("OUT_DIR"), "/input.txt")))
+}
 
-    let side = input.lines().next().unwrap().len();
+pub fn solve_input(input: &str) -> (u32, u32) {
+    let map = parse(input);
 
-    let mut antennas_by_frequency = vec![vec![]; 256];
-    input.bytes().filter(|&b| b != b'\n').enumerate().for_each(|(i, b)| {
-        if b.is_ascii_alphanumeric() {
-            antennas_by_frequency[b as usize].push(((i / side) as Coord, (i % side) as Coord));
+    let mut antinodes_part1 = Bitmap::new(map.height, map.width);
+    let mut antinodes_part2 = Bitmap::new(map.height, map.width);
+
+    aoc_trace::span!("antinodes");
+    map.for_each_antinode(|_, _, _, k, (y, x)| {
+        if k == 2 {
+            antinodes_part1.insert(y, x);
         }
+        antinodes_part2.insert(y, x);
     });
 
-    let mut antinodes_part1 = vec![0u64; side];
-    let mut antinodes_part2 = vec![0u64; side];
+    (antinodes_part1.len(), antinodes_part2.len())
+}
+
+pub fn part1() -> impl Display {
+    count_antinodes(&parse(include_str!(concat!(env!("OUT_DIR"), "/input.txt"))), |k| k == 2)
+}
 
-    for antennas in &antennas_by_frequency {
-        for (y1, x1) in antennas.iter() {
-            for (y2, x2) in antennas.iter() {
-                let dy = y2 - y1;
-                let dx = x2 - x1;
-                if (dy, dx) == (0, 0) {
-                    continue;
-                }
+pub fn part2() -> impl Display {
+    count_antinodes(&parse(include_str!(concat!(env!("OUT_DIR"), "/input.txt"))), |_| true)
+}
 
-                for k in 0.. {
-                    let y0 = y1 + k * dy;
-                    let x0 = x1 + k * dx;
+/// The number of cells with an antinode that `rule` counts, given how many steps it is from its antenna.
+fn count_antinodes(map: &Map, rule: impl Fn(Coord) -> bool) -> u32 {
+    aoc_trace::span!("antinodes");
+    let mut antinodes = Bitmap::new(map.height, map.width);
+    map.for_each_antinode(|_, _, _, k, (y, x)| {
+        if rule(k) {
+            antinodes.insert(y, x);
+        }
+    });
+    antinodes.len()
+}
+
+/// An antinode, and the antenna on the same cell, if any.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Antinode {
+    pub pos: (usize, usize),
+    pub antenna: Option<char>,
+}
 
-                    if (0..side as Coord).contains(&x0) && (0..side as Coord).contains(&y0) {
-                        if k == 2 {
-                            antinodes_part1[y0 as usize] |= 1 << x0;
+impl Display for Antinode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.pos.0, self.pos.1)?;
+        if let Some(antenna) = self.antenna {
+            write!(f, "[{antenna}]")?;
+        }
+        Ok(())
+    }
+}
+
+/// Two antennas of the same frequency, and the antinodes they produce on either side, sorted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pair {
+    pub antennas: [(usize, usize); 2],
+    /// The antinodes twice as far from one antenna as from the other.
+    pub part1: Vec<Antinode>,
+    /// The antinodes a whole number of steps from either antenna along the line through both.
+    pub part2: Vec<Antinode>,
+}
+
+/// The antennas of one frequency in reading order, and every pair of them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frequency {
+    pub frequency: char,
+    pub antennas: Vec<(usize, usize)>,
+    pub pairs: Vec<Pair>,
+}
+
+impl Display for Frequency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |antinodes: &[Antinode]| antinodes.iter().map(Antinode::to_string).collect::<Vec<_>>().join(" ");
+
+        write!(f, "{}: {} antennas", self.frequency, self.antennas.len())?;
+        for pair in &self.pairs {
+            let [(y1, x1), (y2, x2)] = pair.antennas;
+            write!(
+                f,
+                "\n  {y1},{x1} & {y2},{x2}: part 1 {}; part 2 {}",
+                join(&pair.part1),
+                join(&pair.part2)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Every frequency on the map with the antinodes its antenna pairs produce, with antinodes that land on an antenna
+/// marked with it.
+pub fn report(input: &str) -> Vec<Frequency> {
+    let map = parse(input);
+    let rows = input.lines().map(str::as_bytes).collect::<Vec<_>>();
+
+    let mut pairs = BTreeMap::new();
+    map.for_each_antinode(|frequency, i, j, k, (y, x)| {
+        let pair = pairs.entry((frequency, i.min(j), i.max(j))).or_insert((vec![], vec![]));
+        let antinode = Antinode {
+            pos: (y, x),
+            antenna: Some(rows[y][x] as char).filter(char::is_ascii_alphanumeric),
+        };
+        if k == 2 {
+            pair.0.push(antinode);
+        }
+        pair.1.push(antinode);
+    });
+
+    let position = |(y, x): (Coord, Coord)| (y as usize, x as usize);
+    let mut frequencies = map
+        .antennas_by_frequency
+        .iter()
+        .enumerate()
+        .filter(|(_, antennas)| !antennas.is_empty())
+        .map(|(frequency, antennas)| Frequency {
+            frequency: frequency as u8 as char,
+            antennas: antennas.iter().copied().map(position).collect(),
+            pairs: vec![],
+        })
+        .collect::<Vec<_>>();
+    for ((frequency, i, j), (mut part1, mut part2)) in pairs {
+        let report = frequencies
+            .iter_mut()
+            .find(|report| report.frequency == frequency as char)
+            .unwrap();
+        part1.sort();
+        part2.sort();
+        part2.dedup();
+        report.pairs.push(Pair {
+            antennas: [report.antennas[i], report.antennas[j]],
+            part1,
+            part2,
+        });
+    }
+    frequencies
+}
+
+/// The map with the antinodes of `part` drawn as `#`, apart from where there's an antenna, like the puzzle shows them.
+pub fn render(input: &str, part: u8) -> String {
+    let map = parse(input);
+    let mut rows = input.lines().map(|line| line.as_bytes().to_vec()).collect::<Vec<_>>();
+
+    map.for_each_antinode(|_, _, _, k, (y, x)| {
+        if (part == 2 || k == 2) && rows[y][x] == b'.' {
+            rows[y][x] = b'#';
+        }
+    });
+
+    rows.into_iter()
+        .map(|row| String::from_utf8(row).unwrap() + "\n")
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use rand::prelude::*;
+
+    use super::*;
+
+    const EXAMPLE: &str = "\
+............
+........0...
+.....0......
+.......0....
+....0.......
+......A.....
+............
+............
+........A...
+.........A..
+............
+............
+";
+
+    /// Count the antinodes by checking every cell against every pair of antennas.
+    fn naive_solve(input: &str) -> (usize, usize) {
+        let map = input.lines().map(str::as_bytes).collect::<Vec<_>>();
+        let antennas = map
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, &c)| (c, y as i64, x as i64)))
+            .filter(|(c, _, _)| c.is_ascii_alphanumeric())
+            .collect::<Vec<_>>();
+
+        let mut part1 = BTreeSet::new();
+        let mut part2 = BTreeSet::new();
+        for y in 0..map.len() as i64 {
+            for x in 0..map[0].len() as i64 {
+                for &(c1, y1, x1) in &antennas {
+                    for &(c2, y2, x2) in &antennas {
+                        if c1 != c2 || (y1, x1) == (y2, x2) {
+                            continue;
+                        }
+                        // A whole number of steps from the first antenna towards the second.
+                        let (dy, dx) = (y2 - y1, x2 - x1);
+                        let k = if dy != 0 { (y - y1) / dy } else { (x - x1) / dx };
+                        if k >= 0 && (y - y1, x - x1) == (k * dy, k * dx) {
+                            part2.insert((y, x));
+                            if k == 2 {
+                                part1.insert((y, x));
+                            }
                         }
-                        antinodes_part2[y0 as usize] |= 1 << x0;
-                    } else {
-                        break;
                     }
                 }
             }
         }
+        (part1.len(), part2.len())
+    }
+
+    /// A map with `antennas` antennas of a few frequencies at pseudo-random places.
+    fn generate(height: usize, width: usize, antennas: usize, seed: u64) -> String {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut map = vec![vec![b'.'; width]; height];
+        for i in 0..antennas {
+            map[rng.gen_range(0..height)][rng.gen_range(0..width)] = b"aB0"[i % 3];
+        }
+        map.into_iter()
+            .map(|row| String::from_utf8(row).unwrap() + "\n")
+            .collect()
+    }
+
+    #[test]
+    fn test_example() {
+        assert_eq!(solve_input(EXAMPLE), (14, 34));
+        assert_eq!(naive_solve(EXAMPLE), (14, 34));
+    }
+
+    #[test]
+    fn test_wide_maps() {
+        for (height, width, seed) in [(12, 200, 1), (5, 64, 2), (9, 65, 3), (150, 17, 4), (40, 130, 5)] {
+            let input = generate(height, width, 18, seed);
+            let (part1, part2) = naive_solve(&input);
+            assert_eq!(solve_input(&input), (part1 as u32, part2 as u32), "{height}x{width}");
+        }
+    }
+
+    #[test]
+    fn test_report() {
+        let frequencies = report(EXAMPLE);
+        assert_eq!(frequencies.iter().map(|f| f.frequency).collect::<String>(), "0A");
+        assert_eq!(frequencies[1].antennas, [(5, 6), (8, 8), (9, 9)]);
+        assert_eq!(
+            frequencies[1].to_string(),
+            "\
+A: 3 antennas
+  5,6 & 8,8: part 1 2,4 11,10; part 2 2,4 5,6[A] 8,8[A] 11,10
+  5,6 & 9,9: part 1 1,3; part 2 1,3 5,6[A] 9,9[A]
+  8,8 & 9,9: part 1 7,7 10,10; part 2 0,0 1,1 2,2 3,3 4,4[0] 5,5 6,6 7,7 8,8[A] 9,9[A] 10,10 11,11"
+        );
+        assert_eq!(
+            frequencies[0].pairs[1],
+            Pair {
+                antennas: [(1, 8), (3, 7)],
+                part1: vec![Antinode {
+                    pos: (5, 6),
+                    antenna: Some('A')
+                }],
+                part2: [
+                    (1, 8, Some('0')),
+                    (3, 7, Some('0')),
+                    (5, 6, Some('A')),
+                    (7, 5, None),
+                    (9, 4, None),
+                    (11, 3, None)
+                ]
+                .map(|(y, x, antenna)| Antinode { pos: (y, x), antenna })
+                .to_vec(),
+            }
+        );
+
+        for input in [EXAMPLE.to_string(), generate(20, 150, 30, 6)] {
+            let distinct = |part: fn(&Pair) -> &Vec<Antinode>| {
+                let frequencies = report(&input);
+                let antinodes = frequencies.iter().flat_map(|f| &f.pairs).flat_map(part).map(|a| a.pos);
+                antinodes.collect::<BTreeSet<_>>().len() as u32
+            };
+            assert_eq!(
+                (distinct(|pair| &pair.part1), distinct(|pair| &pair.part2)),
+                solve_input(&input)
+            );
+        }
     }
 
-    (
-        antinodes_part1.into_iter().map(|n| n.count_ones()).sum::<u32>(),
-        antinodes_part2.into_iter().map(|n| n.count_ones()).sum::<u32>(),
-    )
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            render(EXAMPLE, 1),
+            "\
+......#....#
+...#....0...
+....#0....#.
+..#....0....
+....0....#..
+.#....A.....
+...#........
+#......#....
+........A...
+.........A..
+..........#.
+..........#.
+"
+        );
+        assert_eq!(
+            render(EXAMPLE, 2),
+            "\
+##....#....#
+.#.#....0...
+..#.#0....#.
+..##...0....
+....0....#..
+.#...#A....#
+...#..#.....
+#....#.#....
+..#.....A...
+....#....A..
+.#........#.
+...#......##
+"
+        );
+    }
 }
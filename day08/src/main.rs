@@ -1,5 +1,24 @@
-fn main() {
-    let (part1, part2) = day08::solve();
-    println!("{part1}");
-    println!("{part2}");
+use std::{env, process::ExitCode};
+
+fn main() -> ExitCode {
+    let input = include_str!(concat!(env!("OUT_DIR"), "/input.txt"));
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [] => {
+            let (part1, part2) = day08::solve();
+            println!("{part1}");
+            println!("{part2}");
+        }
+        ["--pairs"] => {
+            for frequency in day08::report(input) {
+                println!("{frequency}");
+            }
+        }
+        ["--render", part @ ("1" | "2")] => print!("{}", day08::render(input, part.parse().unwrap())),
+        _ => {
+            eprintln!("usage: day08 [--pairs | --render 1|2]");
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
 }
@@ -1,123 +1,477 @@
-use std::{cmp::Reverse, fmt::Display, iter::repeat_n};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BinaryHeap},
+    fmt::{self, Display},
+    ops::ControlFlow,
+};
 
-const EMPTY: i16 = -1;
+/// A run of blocks belonging to one file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub id: u32,
+    pub start: u64,
+    pub len: u32,
+}
 
-#[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    rayon::join(solve_part1, solve_part2)
+impl Span {
+    fn end(&self) -> u64 {
+        self.start + u64::from(self.len)
+    }
+
+    /// The sum of each block's position times the file's ID.
+    fn checksum(&self) -> u64 {
+        let len = u64::from(self.len);
+        u64::from(self.id) * (len * self.start + len * len.saturating_sub(1) / 2)
+    }
+}
+
+/// A run of free blocks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Gap {
+    pub start: u64,
+    pub len: u32,
 }
 
-fn solve_part1() -> u64 {
-    let input = include_str!("input.txt").trim();
+/// The disk map, as the files in ID order and the gaps between them in disk order, so that gap `i` follows file `i`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Disk {
+    pub files: Vec<Span>,
+    pub gaps: Vec<Gap>,
+}
 
-    let mut disk = Vec::new();
-    let mut first_free = usize::MAX;
-    let mut last_used = 0;
-    let mut next_id = 0;
+impl Disk {
+    pub fn parse(input: &str) -> Self {
+        let mut files = Vec::with_capacity(input.len() / 2 + 1);
+        let mut gaps = Vec::with_capacity(input.len() / 2);
+        let mut start = 0;
 
-    for (i, val) in input.bytes().enumerate() {
-        let val = (val - b'0') as usize;
+        for (i, val) in input.trim().bytes().enumerate() {
+            let len = u32::from(val - b'0');
 
-        if i % 2 == 0 {
-            disk.extend(repeat_n(next_id, val));
-            last_used = disk.len() - 1;
-            next_id += 1;
-        } else {
-            disk.extend(repeat_n(EMPTY, val));
-            if first_free == usize::MAX {
-                first_free = disk.len() - val;
+            if i % 2 == 0 {
+                files.push(Span {
+                    id: files.len() as u32,
+                    start,
+                    len,
+                });
+            } else {
+                gaps.push(Gap { start, len });
             }
+            start += u64::from(len);
         }
+
+        Self { files, gaps }
     }
 
-    while first_free < last_used {
-        disk.swap(first_free, last_used);
-        while disk[first_free] != EMPTY {
-            first_free += 1;
-        }
-        while disk[last_used] == EMPTY {
-            last_used -= 1;
-        }
+    /// The number of blocks on the disk, free or not.
+    pub fn blocks(&self) -> u64 {
+        let files = self.files.last().map_or(0, Span::end);
+        let gaps = self.gaps.last().map_or(0, |gap| gap.start + u64::from(gap.len));
+        files.max(gaps)
+    }
+
+    /// The file spans after compacting the disk with `strategy`.
+    pub fn compact(&self, strategy: &dyn Strategy) -> Vec<Span> {
+        strategy.compact(self, &mut Moves::default())
     }
+}
 
-    checksum(disk)
+/// Blocks of a file moving from one place on the disk to another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Move {
+    pub id: u32,
+    pub from: u64,
+    pub to: u64,
+    pub len: u32,
 }
 
-fn solve_part2() -> u64 {
-    let input = include_str!("input.txt").trim();
+/// Where a strategy reports its moves to, if anywhere.
+///
+/// Compacting block by block makes a move for every block, so when nothing is listening, as when solving the puzzle,
+/// it moves as many blocks as fit at once instead.
+#[derive(Default)]
+pub struct Moves<'a> {
+    on_move: Option<&'a mut dyn FnMut(Move) -> ControlFlow<()>>,
+}
 
-    let mut next_id = 0;
-    let mut spaces: [std::collections::BinaryHeap<Reverse<usize>>; 10] = std::array::from_fn(|_| Default::default());
+impl<'a> Moves<'a> {
+    /// Report each move to `on_move`, stopping the compaction there if it breaks.
+    pub fn new(on_move: &'a mut dyn FnMut(Move) -> ControlFlow<()>) -> Self {
+        Self { on_move: Some(on_move) }
+    }
 
-    let mut disk = Vec::new();
-    for (i, val) in input.bytes().enumerate() {
-        let val = (val - b'0') as usize;
+    fn is_recording(&self) -> bool {
+        self.on_move.is_some()
+    }
 
-        if i % 2 == 0 {
-            // It's a file
-            disk.extend(repeat_n(next_id, val));
-            next_id += 1;
-        } else {
-            // It's free space
-            // push the start of this free space block
-            let start_idx = disk.len();
-            spaces[val].push(Reverse(start_idx));
-            disk.extend(repeat_n(EMPTY, val));
+    #[inline]
+    fn record(&mut self, step: Move) -> ControlFlow<()> {
+        match &mut self.on_move {
+            Some(on_move) => on_move(step),
+            None => ControlFlow::Continue(()),
         }
     }
+}
+
+/// A way of compacting the disk, one move at a time.
+pub trait Strategy: fmt::Debug + Sync {
+    fn name(&self) -> &'static str;
+
+    /// The file spans after compacting `disk`, recording each move in `moves` as it's made; if that breaks, the
+    /// compaction stops there and the spans are returned as they are.
+    fn compact(&self, disk: &Disk, moves: &mut Moves<'_>) -> Vec<Span>;
+}
+
+/// Move blocks one at a time from the end of the disk to the leftmost free block, splitting files up as needed.
+#[derive(Debug)]
+pub struct Blocks;
+
+impl Strategy for Blocks {
+    fn name(&self) -> &'static str {
+        "blocks"
+    }
+
+    fn compact(&self, disk: &Disk, moves: &mut Moves<'_>) -> Vec<Span> {
+        let mut files = disk.files.clone();
+        let mut moved = Vec::new();
+
+        // Gap `g` lies between files `g` and `g + 1`, so it's left of the last file for as long as that one is there.
+        let mut gaps = disk.gaps.iter().copied().enumerate();
+        let Some((mut g, mut gap)) = gaps.next() else {
+            return files;
+        };
+        while g + 1 < files.len() {
+            let last = files.last_mut().unwrap();
+            let fits = gap.len.min(last.len);
+            let mut len = fits;
+            let mut flow = ControlFlow::Continue(());
+            if moves.is_recording() {
+                // Each block is a move of its own, and the compaction may stop after any of them.
+                len = 0;
+                while len < fits && flow.is_continue() {
+                    len += 1;
+                    flow = moves.record(Move {
+                        id: last.id,
+                        from: last.end() - u64::from(len),
+                        to: gap.start + u64::from(len - 1),
+                        len: 1,
+                    });
+                }
+            }
+            if len > 0 {
+                moved.push(Span {
+                    id: last.id,
+                    start: gap.start,
+                    len,
+                });
+                last.len -= len;
+                gap.start += u64::from(len);
+                gap.len -= len;
+            }
 
-    let mut i = disk.len() - 1;
-    while i != 0 {
-        if disk[i] == EMPTY {
-            i -= 1;
-            continue;
+            if last.len == 0 {
+                files.pop();
+            }
+            if flow.is_break() {
+                break;
+            }
+            if gap.len == 0 {
+                match gaps.next() {
+                    Some(next) => (g, gap) = next,
+                    None => break,
+                }
+            }
         }
 
-        let file_id = disk[i];
-        let mut file_size = 0;
-        while disk[i] == file_id {
-            file_size += 1;
-            if i == 0 {
+        files.append(&mut moved);
+        files
+    }
+}
+
+/// The starts of the gaps of each length, with the leftmost on top.
+type Spaces = [BinaryHeap<Reverse<u64>>; 10];
+
+/// Move whole files, from the highest ID down, to a gap left of them that `choose` picks out of `spaces` as the start
+/// and length of the gap.
+fn move_files(disk: &Disk, moves: &mut Moves<'_>, choose: fn(&Spaces, &Span) -> Option<(u64, usize)>) -> Vec<Span> {
+    let mut spaces: Spaces = Default::default();
+    for gap in &disk.gaps {
+        spaces[gap.len as usize].push(Reverse(gap.start));
+    }
+
+    let mut files = disk.files.clone();
+    for file in files.iter_mut().rev() {
+        if let Some((start, width)) = choose(&spaces, file) {
+            spaces[width].pop();
+            let leftover = width - file.len as usize;
+            if leftover > 0 {
+                spaces[leftover].push(Reverse(start + u64::from(file.len)));
+            }
+
+            let flow = moves.record(Move {
+                id: file.id,
+                from: file.start,
+                to: start,
+                len: file.len,
+            });
+            file.start = start;
+            if flow.is_break() {
                 break;
-            } else {
-                i -= 1;
             }
         }
+    }
 
-        if let Some((smallest_i, best_width)) = (file_size..10)
-            .filter_map(|size| spaces[size].peek().copied().map(|i| (i.0, size)))
-            .min_by_key(|&(i, _)| i)
-            .filter(|&(j, _)| j <= i)
-        {
-            disk[smallest_i..smallest_i + file_size].fill(file_id);
-            disk[i + 1..i + 1 + file_size].fill(EMPTY);
+    files
+}
+
+/// Move whole files, from the highest ID down, to the leftmost gap they fit in.
+#[derive(Debug)]
+pub struct FirstFit;
 
-            spaces[best_width].pop();
+impl Strategy for FirstFit {
+    fn name(&self) -> &'static str {
+        "first-fit"
+    }
 
-            if best_width > file_size {
-                let leftover_start = smallest_i + file_size;
-                let leftover_size = best_width - file_size;
-                spaces[leftover_size].push(Reverse(leftover_start));
+    fn compact(&self, disk: &Disk, moves: &mut Moves<'_>) -> Vec<Span> {
+        move_files(disk, moves, |spaces, file| {
+            (file.len as usize..10)
+                .filter_map(|width| spaces[width].peek().map(|&Reverse(start)| (start, width)))
+                .min_by_key(|&(start, _)| start)
+                .filter(|&(start, _)| start < file.start)
+        })
+    }
+}
+
+/// Move whole files, from the highest ID down, to the smallest gap left of them that they fit in, the leftmost one if
+/// there are several.
+#[derive(Debug)]
+pub struct BestFit;
+
+impl Strategy for BestFit {
+    fn name(&self) -> &'static str {
+        "best-fit"
+    }
+
+    fn compact(&self, disk: &Disk, moves: &mut Moves<'_>) -> Vec<Span> {
+        move_files(disk, moves, |spaces, file| {
+            (file.len as usize..10)
+                .filter_map(|width| spaces[width].peek().map(|&Reverse(start)| (start, width)))
+                .find(|&(start, _)| start < file.start)
+        })
+    }
+}
+
+/// Move whole files, from the highest ID down, to the leftmost gap they fit in, counting the space left behind by
+/// files that moved, and go over them again until none of them can move.
+///
+/// Gaps grow as files move out of their way, so they're kept in order of where they are rather than by length, and
+/// each file looks through them all: it's quadratic, unlike the others.
+#[derive(Debug)]
+pub struct Defrag;
+
+impl Strategy for Defrag {
+    fn name(&self) -> &'static str {
+        "defrag"
+    }
+
+    fn compact(&self, disk: &Disk, moves: &mut Moves<'_>) -> Vec<Span> {
+        let mut gaps = disk
+            .gaps
+            .iter()
+            .filter(|gap| gap.len > 0)
+            .map(|gap| (gap.start, u64::from(gap.len)))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut files = disk.files.clone();
+        let mut moved = true;
+        while moved {
+            moved = false;
+            for file in files.iter_mut().rev() {
+                let len = u64::from(file.len);
+                let Some((&start, &width)) = gaps.range(..file.start).find(|&(_, &width)| width >= len) else {
+                    continue;
+                };
+                gaps.remove(&start);
+                if width > len {
+                    gaps.insert(start + len, width - len);
+                }
+
+                // Free up the space the file was in, joining it up with the gaps on either side.
+                let (mut free, mut width) = (file.start, len);
+                if let Some((&before, &before_width)) = gaps.range(..free).next_back() {
+                    if before + before_width == free {
+                        gaps.remove(&before);
+                        (free, width) = (before, before_width + width);
+                    }
+                }
+                if let Some(after_width) = gaps.remove(&(free + width)) {
+                    width += after_width;
+                }
+                gaps.insert(free, width);
+
+                let flow = moves.record(Move {
+                    id: file.id,
+                    from: file.start,
+                    to: start,
+                    len: file.len,
+                });
+                file.start = start;
+                moved = true;
+                if flow.is_break() {
+                    return files;
+                }
             }
         }
+
+        files
     }
+}
+
+pub const PART1: &dyn Strategy = &Blocks;
+pub const PART2: &dyn Strategy = &FirstFit;
+
+pub const STRATEGIES: [&dyn Strategy; 4] = [&Blocks, &FirstFit, &BestFit, &Defrag];
+
+#[inline]
+pub fn solve() -> (impl Display, impl Display) {
+    solve_input(include_str!(concat!(env!("OUT_DIR"), "/input.txt")))
+}
+
+pub fn solve_input(input: &str) -> (impl Display, impl Display) {
+    let disk = aoc_trace::span!("parse", Disk::parse(input));
+    rayon::join(|| solve_part1(&disk), || solve_part2(&disk))
+}
+
+pub fn part1() -> impl Display {
+    solve_part1(&Disk::parse(include_str!(concat!(env!("OUT_DIR"), "/input.txt"))))
+}
+
+pub fn part2() -> impl Display {
+    solve_part2(&Disk::parse(include_str!(concat!(env!("OUT_DIR"), "/input.txt"))))
+}
+
+fn solve_part1(disk: &Disk) -> u64 {
+    aoc_trace::span!("part1");
+    let files = aoc_trace::span!("compact", disk.compact(PART1));
+    aoc_trace::span!("checksum", checksum(&files))
+}
 
-    checksum(disk)
+fn solve_part2(disk: &Disk) -> u64 {
+    aoc_trace::span!("part2");
+    let files = aoc_trace::span!("compact", disk.compact(PART2));
+    aoc_trace::span!("checksum", checksum(&files))
 }
 
-fn checksum(file_system: Vec<i16>) -> u64 {
-    file_system
-        .into_iter()
-        .enumerate()
-        .filter(|&(_, f)| f != EMPTY)
-        .map(|(i, f)| i as u64 * f as u64)
-        .sum()
+pub fn checksum(files: &[Span]) -> u64 {
+    files.iter().map(Span::checksum).sum()
+}
+
+/// The disk as the puzzle shows it, with each block as the last digit of its file's ID, or `.` if it's free.
+pub fn render(blocks: u64, files: &[Span]) -> String {
+    let mut disk = vec![b'.'; blocks as usize];
+    for file in files {
+        disk[file.start as usize..file.end() as usize].fill(b'0' + (file.id % 10) as u8);
+    }
+    String::from_utf8(disk).unwrap()
+}
+
+/// The disk before compacting it with `strategy` and after each of the first `limit` moves, or all of them.
+pub fn render_steps(disk: &Disk, strategy: &dyn Strategy, limit: Option<usize>) -> Vec<String> {
+    let mut blocks = render(disk.blocks(), &disk.files).into_bytes();
+    let mut steps = vec![String::from_utf8(blocks.clone()).unwrap()];
+    if limit == Some(0) {
+        return steps;
+    }
+
+    let mut on_move = |step: Move| {
+        let (from, to) = (step.from as usize, step.to as usize);
+        for i in 0..step.len as usize {
+            blocks[to + i] = blocks[from + i];
+            blocks[from + i] = b'.';
+        }
+        steps.push(String::from_utf8(blocks.clone()).unwrap());
+
+        match limit {
+            Some(limit) if steps.len() > limit => ControlFlow::Break(()),
+            _ => ControlFlow::Continue(()),
+        }
+    };
+    strategy.compact(disk, &mut Moves::new(&mut on_move));
+    steps
 }
 
 #[cfg(test)]
 mod tests {
+    use rand::prelude::*;
+
     use super::*;
 
+    const EXAMPLE: &str = "2333133121414131402";
+
+    /// Compact the disk one block at a time, the way the puzzle describes it.
+    fn naive_solve(input: &str) -> (u64, u64) {
+        let mut disk = Vec::new();
+        for (i, val) in input.bytes().enumerate() {
+            let id = (i % 2 == 0).then_some(i as u64 / 2);
+            disk.extend(std::iter::repeat_n(id, (val - b'0') as usize));
+        }
+        let checksum = |disk: &[Option<u64>]| disk.iter().enumerate().map(|(i, id)| i as u64 * id.unwrap_or(0)).sum();
+
+        let mut part1 = disk.clone();
+        let (mut free, mut used) = (0, part1.len() - 1);
+        while free < used {
+            match (part1[free], part1[used]) {
+                (Some(_), _) => free += 1,
+                (_, None) => used -= 1,
+                _ => part1.swap(free, used),
+            }
+        }
+
+        // Files haven't moved yet by the time it's their turn, so they're still where the disk map puts them.
+        let mut part2 = disk;
+        let mut starts = vec![];
+        let mut searched = [0; 10];
+        input.bytes().fold(0, |start, val| {
+            starts.push(start);
+            start + (val - b'0') as usize
+        });
+        for (id, &start) in starts.iter().enumerate().step_by(2).rev() {
+            let len = (input.as_bytes()[id] - b'0') as usize;
+            let id = id as u64 / 2;
+            // Nothing left of a file ever frees up, so the leftmost window of each length only moves right.
+            let from = searched[len].min(start);
+            match part2[from..start]
+                .windows(len)
+                .position(|window| window.iter().all(Option::is_none))
+            {
+                Some(free) => {
+                    searched[len] = from + free;
+                    part2[from + free..from + free + len].fill(Some(id));
+                    part2[start..start + len].fill(None);
+                }
+                None => searched[len] = start,
+            }
+        }
+
+        (checksum(&part1), checksum(&part2))
+    }
+
+    /// A disk map of `len` digits, with files of 1 to 9 blocks like the puzzle's and gaps of 0 to 9.
+    fn generate(len: usize, seed: u64) -> String {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..len)
+            .map(|i| {
+                let val = if i % 2 == 0 {
+                    rng.gen_range(1..=9)
+                } else {
+                    rng.gen_range(0..=9)
+                };
+                (b'0' + val) as char
+            })
+            .collect()
+    }
+
     #[test]
     fn test_day() {
         let (p1, p2) = solve();
@@ -125,4 +479,120 @@ mod tests {
         assert_eq!(p1.to_string(), expected.0.to_string());
         assert_eq!(p2.to_string(), expected.1.to_string());
     }
+
+    #[test]
+    fn test_example() {
+        let disk = Disk::parse(EXAMPLE);
+        assert_eq!(checksum(&disk.compact(PART1)), 1928);
+        assert_eq!(checksum(&disk.compact(PART2)), 2858);
+        assert_eq!(naive_solve(EXAMPLE), (1928, 2858));
+    }
+
+    #[test]
+    fn test_many_files() {
+        for (len, seed) in [(1, 1), (2, 2), (31, 3), (200, 4), (80_001, 5), (80_000, 6)] {
+            let input = generate(len, seed);
+            let disk = Disk::parse(&input);
+            let expected = naive_solve(&input);
+            assert_eq!(
+                (checksum(&disk.compact(PART1)), checksum(&disk.compact(PART2))),
+                expected,
+                "{len} digits"
+            );
+        }
+        assert_eq!(Disk::parse(&generate(80_001, 5)).files.last().unwrap().id, 40_000);
+    }
+
+    #[test]
+    fn test_render_steps() {
+        assert_eq!(
+            render_steps(&Disk::parse("12345"), &Blocks, None),
+            [
+                "0..111....22222",
+                "02.111....2222.",
+                "022111....222..",
+                "0221112...22...",
+                "02211122..2....",
+                "022111222......",
+            ]
+        );
+        assert_eq!(
+            render_steps(&Disk::parse(EXAMPLE), &FirstFit, None),
+            [
+                "00...111...2...333.44.5555.6666.777.888899",
+                "0099.111...2...333.44.5555.6666.777.8888..",
+                "0099.1117772...333.44.5555.6666.....8888..",
+                "0099.111777244.333....5555.6666.....8888..",
+                "00992111777.44.333....5555.6666.....8888..",
+            ]
+        );
+        assert_eq!(
+            render_steps(&Disk::parse(EXAMPLE), &Blocks, Some(2)),
+            [
+                "00...111...2...333.44.5555.6666.777.888899",
+                "009..111...2...333.44.5555.6666.777.88889.",
+                "0099.111...2...333.44.5555.6666.777.8888..",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_strategies() {
+        let disk = Disk::parse("15122");
+        assert_eq!(render(disk.blocks(), &disk.compact(&FirstFit)), "0221.......");
+        assert_eq!(render(disk.blocks(), &disk.compact(&BestFit)), "01.....22..");
+
+        // The 8s only fit once the 4s have moved out of their way.
+        let disk = Disk::parse(EXAMPLE);
+        assert_eq!(
+            render(disk.blocks(), &disk.compact(&Defrag)),
+            "00992111777.44.33388885555.6666..........."
+        );
+
+        for input in [EXAMPLE, "12345", "15122"] {
+            let disk = Disk::parse(input);
+            for strategy in STRATEGIES {
+                // Stopping after a number of moves leaves the files where that many steps of the rendering show them.
+                let steps = render_steps(&disk, strategy, None);
+                for limit in 1..steps.len() {
+                    let mut moves = 0;
+                    let mut on_move = |_| {
+                        moves += 1;
+                        if moves < limit {
+                            ControlFlow::Continue(())
+                        } else {
+                            ControlFlow::Break(())
+                        }
+                    };
+                    let files = strategy.compact(&disk, &mut Moves::new(&mut on_move));
+                    assert_eq!(
+                        render(disk.blocks(), &files),
+                        steps[limit],
+                        "{} {input}",
+                        strategy.name()
+                    );
+                    assert_eq!(render_steps(&disk, strategy, Some(limit)), steps[..=limit]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_defrag() {
+        let disk = Disk::parse(&generate(300, 7));
+        let mut files = disk.compact(&Defrag);
+        let after = render(disk.blocks(), &files);
+        files.sort_by_key(|file| file.start);
+
+        assert_eq!(files.len(), disk.files.len());
+        for (file, next) in files.iter().zip(&files[1..]) {
+            assert!(file.end() <= next.start);
+        }
+        for file in &files {
+            assert_eq!(file.len, disk.files[file.id as usize].len);
+            assert!(file.start <= disk.files[file.id as usize].start);
+            let gap = ".".repeat(file.len as usize);
+            assert!(!after[..file.start as usize].contains(&gap), "{after}");
+        }
+    }
 }
@@ -1,5 +1,35 @@
-fn main() {
-    let (part1, part2) = day09::solve();
-    println!("{part1}");
-    println!("{part2}");
+use std::{env, process::ExitCode};
+
+use day09::Disk;
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let (render, name, limit) = match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [] => {
+            let (part1, part2) = day09::solve();
+            println!("{part1}");
+            println!("{part2}");
+            return ExitCode::SUCCESS;
+        }
+        ["--checksum", name] => (false, name, Some(None)),
+        ["--render", name] => (true, name, Some(None)),
+        ["--render", name, limit] => (true, name, limit.parse().ok().map(Some)),
+        _ => (false, "", None),
+    };
+    let strategy = day09::STRATEGIES.into_iter().find(|strategy| strategy.name() == name);
+    let (Some(strategy), Some(limit)) = (strategy, limit) else {
+        let names = day09::STRATEGIES.map(|strategy| strategy.name()).join("|");
+        eprintln!("usage: day09 [--checksum {names} | --render {names} [STEPS]]");
+        return ExitCode::FAILURE;
+    };
+
+    let disk = Disk::parse(include_str!(concat!(env!("OUT_DIR"), "/input.txt")));
+    if render {
+        for step in day09::render_steps(&disk, strategy, limit) {
+            println!("{step}");
+        }
+    } else {
+        println!("{}", day09::checksum(&disk.compact(strategy)));
+    }
+    ExitCode::SUCCESS
 }
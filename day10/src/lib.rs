@@ -1,61 +1,253 @@
 use std::fmt::Display;
 
+use grid::Grid;
 use rayon::prelude::*;
 
 const START: u8 = 0;
 const END: u8 = 9;
 
+/// What anything that isn't a height, like the puzzle's `.`, is parsed as, so that no trail goes through it.
+const IMPASSABLE: u8 = u8::MAX;
+
 const DIRS: [(isize, isize); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
 
-struct State {
-    pos: (usize, usize),
+/// The cells next to `(y, x)` that are one higher.
+fn uphill(grid: &Grid<u8>, (y, x): (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+    let cell = grid[(y, x)];
+
+    DIRS.into_iter().filter_map(move |(dy, dx)| {
+        let new_y = y.checked_add_signed(dy)?;
+        let new_x = x.checked_add_signed(dx)?;
+        let &new_cell = grid.get(new_y, new_x)?;
+        (new_cell == cell + 1).then_some((new_y, new_x))
+    })
+}
+
+fn parse(input: &str) -> Grid<u8> {
+    let width = input.lines().next().map_or(0, str::len);
+    aoc_trace::span!(
+        "parse",
+        Grid::from_vec(
+            input
+                .lines()
+                .flat_map(str::bytes)
+                .map(|b| if b.is_ascii_digit() { b - b'0' } else { IMPASSABLE })
+                .collect(),
+            width,
+        )
+    )
 }
 
-impl State {
-    fn advance<'a>(&'a self, grid: &'a grid::Grid<u8>) -> impl Iterator<Item = Self> + 'a {
-        let (y, x) = self.pos;
-        let &cell = grid.get(y, x).unwrap();
+fn trailheads(grid: &Grid<u8>) -> Vec<(usize, usize)> {
+    grid.indexed_iter()
+        .filter(|(_, &c)| c == START)
+        .map(|(pos, _)| pos)
+        .collect()
+}
+
+/// The number of summits reachable from each trailhead, summed.
+///
+/// Each trailhead's search stamps the cells it gets to with its own number, so that it goes through each of them once
+/// and the grid of stamps can be shared by all the trailheads a thread searches from without clearing it.
+fn scores(grid: &Grid<u8>, trailheads: &[(usize, usize)]) -> u64 {
+    trailheads
+        .par_iter()
+        .enumerate()
+        .map_init(
+            || (Grid::new(grid.rows(), grid.cols()), vec![]),
+            |(seen, stack): &mut (Grid<u32>, Vec<_>), (i, &trailhead)| {
+                let stamp = i as u32 + 1;
+                seen[trailhead] = stamp;
+                stack.push(trailhead);
+
+                let mut summits = 0;
+                while let Some(pos) = stack.pop() {
+                    if grid[pos] == END {
+                        summits += 1;
+                        continue;
+                    }
+                    for next in uphill(grid, pos) {
+                        if seen[next] != stamp {
+                            seen[next] = stamp;
+                            stack.push(next);
+                        }
+                    }
+                }
+                summits
+            },
+        )
+        .sum()
+}
 
-        DIRS.into_iter().filter_map(move |(dy, dx)| {
-            let new_y = y.checked_add_signed(dy)?;
-            let new_x = x.checked_add_signed(dx)?;
-            let new_pos = (new_y, new_x);
-            let &new_cell = grid.get(new_y, new_x)?;
-            (new_cell == cell + 1).then_some(State { pos: new_pos })
-        })
+/// The number of distinct trails from each trailhead, summed.
+///
+/// The number of trails from a cell to a summit is the sum of those from the cells one higher next to it, so they're
+/// counted a height at a time from the summits down.
+fn ratings(grid: &Grid<u8>, trailheads: &[(usize, usize)]) -> u64 {
+    let mut layers: [Vec<(usize, usize)>; END as usize + 1] = Default::default();
+    for (pos, &cell) in grid.indexed_iter() {
+        if cell <= END {
+            layers[cell as usize].push(pos);
+        }
     }
+
+    let mut trails = Grid::<u64>::new(grid.rows(), grid.cols());
+    for &pos in &layers[END as usize] {
+        trails[pos] = 1;
+    }
+    for layer in layers[..END as usize].iter().rev() {
+        for &pos in layer {
+            trails[pos] = uphill(grid, pos).map(|next| trails[next]).sum();
+        }
+    }
+
+    trailheads.iter().map(|&pos| trails[pos]).sum()
 }
 
 #[inline]
 pub fn solve() -> (impl Display, impl Display) {
-    let input = include_str!("input.txt");
-    let side = input.lines().count();
-    debug_assert!(side < 64);
-    let grid = grid::Grid::from_vec(input.bytes().filter(|&b| b != b'\n').map(|b| b - b'0').collect(), side);
+    solve_input(include_str!(concat!(env!("OUT_DIR"), "/input.txt")))
+}
 
-    grid.indexed_iter()
-        .filter(|(_, &c)| c == START)
-        .map(|(pos, _)| State { pos })
-        .collect::<Vec<_>>()
-        .into_par_iter()
-        .map(|state| {
-            let mut states = vec![state];
-            let mut reachable = vec![0u64; side];
-            let mut p2 = 0;
-
-            while let Some(state) = states.pop() {
-                if let Some(&cell) = grid.get(state.pos.0, state.pos.1) {
-                    if cell == END {
-                        reachable[state.pos.0] |= 1 << state.pos.1;
-                        p2 += 1;
-                    } else {
-                        states.extend(state.advance(&grid));
-                    }
-                }
+pub fn solve_input(input: &str) -> (u64, u64) {
+    let grid = parse(input);
+    let trailheads = trailheads(&grid);
+
+    aoc_trace::span!("trails");
+    rayon::join(|| scores(&grid, &trailheads), || ratings(&grid, &trailheads))
+}
+
+pub fn part1() -> impl Display {
+    let grid = parse(include_str!(concat!(env!("OUT_DIR"), "/input.txt")));
+    scores(&grid, &trailheads(&grid))
+}
+
+pub fn part2() -> impl Display {
+    let grid = parse(include_str!(concat!(env!("OUT_DIR"), "/input.txt")));
+    ratings(&grid, &trailheads(&grid))
+}
+
+/// Every distinct trail on the map, as the cells it goes through from the trailhead to the summit, sorted.
+///
+/// There can be exponentially many of them, so this is for small maps and for checking the ratings.
+pub fn trails(input: &str) -> Vec<Vec<(usize, usize)>> {
+    let grid = parse(input);
+
+    let mut trails = vec![];
+    let mut stack = trailheads(&grid).into_iter().map(|pos| vec![pos]).collect::<Vec<_>>();
+    while let Some(trail) = stack.pop() {
+        let &pos = trail.last().unwrap();
+        if grid[pos] == END {
+            trails.push(trail);
+            continue;
+        }
+        for next in uphill(&grid, pos) {
+            let mut trail = trail.clone();
+            trail.push(next);
+            stack.push(trail);
+        }
+    }
+
+    trails.sort_unstable();
+    trails
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use rand::prelude::*;
+
+    use super::*;
+
+    const EXAMPLE: &str = "\
+89010123
+78121874
+87430965
+96549874
+45678903
+32019012
+01329801
+10456732
+";
+
+    /// A map whose heights mostly go up by one to the right and down, with some noise so that not every path does.
+    fn generate(height: usize, width: usize, seed: u64) -> String {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut map = String::new();
+        for y in 0..height {
+            for x in 0..width {
+                let noise = if rng.gen_ratio(1, 5) { rng.gen_range(0..10) } else { 0 };
+                map.push((b'0' + ((y + x + noise) % 10) as u8) as char);
             }
+            map.push('\n');
+        }
+        map
+    }
+
+    #[test]
+    fn test_example() {
+        assert_eq!(solve_input(EXAMPLE), (36, 81));
+
+        let trails = trails(EXAMPLE);
+        assert_eq!(trails.len(), 81);
+        assert_eq!(
+            trails[0],
+            [
+                (0, 2),
+                (0, 3),
+                (1, 3),
+                (2, 3),
+                (2, 2),
+                (3, 2),
+                (3, 1),
+                (2, 1),
+                (1, 1),
+                (0, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_impassable() {
+        let input = "\
+.....0.
+..4321.
+..5..2.
+..6543.
+..7..4.
+..8765.
+..9....
+";
+        assert_eq!(solve_input(input), (1, 3));
+        assert_eq!(trails(input).len(), 3);
+    }
+
+    #[test]
+    fn test_line_endings() {
+        assert_eq!(solve_input(&EXAMPLE.replace('\n', "\r\n")), (36, 81));
+        assert_eq!(trails(&EXAMPLE.replace('\n', "\r\n")), trails(EXAMPLE));
+        assert_eq!(solve_input(EXAMPLE.trim_end()), (36, 81));
+        assert_eq!(solve_input(""), (0, 0));
+        assert_eq!(trails(""), Vec::<Vec<_>>::new());
+    }
 
-            let p1 = reachable.iter().map(|r| r.count_ones() as u64).sum::<u64>();
-            (p1, p2)
-        })
-        .reduce(|| (0, 0), |(p1, p2), (p1_, p2_)| (p1 + p1_, p2 + p2_))
+    #[test]
+    fn test_wide_maps() {
+        for (height, width, seed) in [(3, 200, 1), (70, 70, 2), (120, 9, 3)] {
+            let input = generate(height, width, seed);
+            let trails = trails(&input);
+            for trail in &trails {
+                for (&(y1, x1), &(y2, x2)) in trail.iter().zip(&trail[1..]) {
+                    assert_eq!(y1.abs_diff(y2) + x1.abs_diff(x2), 1);
+                }
+            }
+            let ends = trails.iter().map(|trail| (trail[0], trail[9])).collect::<BTreeSet<_>>();
+            assert_eq!(
+                solve_input(&input),
+                (ends.len() as u64, trails.len() as u64),
+                "{height}x{width}"
+            );
+        }
+    }
 }
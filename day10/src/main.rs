@@ -1,5 +1,23 @@
-fn main() {
-    let (part1, part2) = day10::solve();
-    println!("{part1}");
-    println!("{part2}");
+use std::{env, process::ExitCode};
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [] => {
+            let (part1, part2) = day10::solve();
+            println!("{part1}");
+            println!("{part2}");
+        }
+        ["--trails"] => {
+            for trail in day10::trails(include_str!(concat!(env!("OUT_DIR"), "/input.txt"))) {
+                let cells = trail.iter().map(|(y, x)| format!("{y},{x}")).collect::<Vec<_>>();
+                println!("{}", cells.join(" -> "));
+            }
+        }
+        _ => {
+            eprintln!("usage: day10 [--trails]");
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
 }
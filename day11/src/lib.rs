@@ -29,13 +29,24 @@ fn stones(n: u64, steps_left: usize) -> usize {
 
 #[inline]
 pub fn solve() -> (impl Display, impl Display) {
-    let input = include_str!("input.txt");
+    aoc_trace::span!("blink");
+    parse()
+        .map(|n| (stones(n, 25), stones(n, 75)))
+        .reduce(|(a, b), (c, d)| (a + c, b + d))
+        .unwrap()
+}
+
+pub fn part1() -> impl Display {
+    parse().map(|n| stones(n, 25)).sum::<usize>()
+}
 
-    input
+pub fn part2() -> impl Display {
+    parse().map(|n| stones(n, 75)).sum::<usize>()
+}
+
+fn parse() -> impl Iterator<Item = u64> {
+    include_str!(concat!(env!("OUT_DIR"), "/input.txt"))
         .trim()
         .split(' ')
         .map(|n| n.parse::<u64>().unwrap())
-        .map(|n| (stones(n, 25), stones(n, 75)))
-        .reduce(|(a, b), (c, d)| (a + c, b + d))
-        .unwrap()
 }
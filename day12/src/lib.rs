@@ -18,104 +18,144 @@ where
 
 #[inline]
 pub fn solve() -> (impl Display, impl Display) {
-    let input = include_str!("input.txt");
-    let grid: Vec<u8> = input.bytes().filter(|&b| b != b'\n').collect();
+    let regions = regions();
+
+    aoc_trace::span!("scan_regions");
+    regions
+        .into_par_iter()
+        .map(|region| {
+            let set = cells(&region);
+            (
+                region.len() * perimeter(&region, &set),
+                region.len() * sides(&region, &set),
+            )
+        })
+        .reduce(|| (0, 0), |(t1_acc, t2_acc), (t1, t2)| (t1_acc + t1, t2_acc + t2))
+}
+
+pub fn part1() -> impl Display {
+    regions()
+        .into_par_iter()
+        .map(|region| region.len() * perimeter(&region, &cells(&region)))
+        .sum::<usize>()
+}
+
+pub fn part2() -> impl Display {
+    regions()
+        .into_par_iter()
+        .map(|region| region.len() * sides(&region, &cells(&region)))
+        .sum::<usize>()
+}
+
+/// The cells of each region, as `(x, y)`.
+fn regions() -> Vec<Vec<(u8, u8)>> {
+    let input = include_str!(concat!(env!("OUT_DIR"), "/input.txt"));
+    let grid: Vec<u8> = aoc_trace::span!("parse", input.bytes().filter(|&b| b != b'\n').collect());
 
     // Find regions
     let mut uf = UnionFind::<usize>::new(SIDE * SIDE);
-    for y in 0..SIDE {
-        for x in 0..SIDE {
-            let current_char = grid[pos2idx(x, y)];
-            let current_index = pos2idx(x, y);
-
-            // Check right neighbor
-            if x + 1 < SIDE && grid[pos2idx(x + 1, y)] == current_char {
-                uf.union(current_index, pos2idx(x + 1, y));
-            }
+    aoc_trace::span!(
+        "union_find",
+        for y in 0..SIDE {
+            for x in 0..SIDE {
+                let current_char = grid[pos2idx(x, y)];
+                let current_index = pos2idx(x, y);
+
+                // Check right neighbor
+                if x + 1 < SIDE && grid[pos2idx(x + 1, y)] == current_char {
+                    uf.union(current_index, pos2idx(x + 1, y));
+                }
 
-            // Check down neighbor
-            if y + 1 < SIDE && grid[pos2idx(x, y + 1)] == current_char {
-                uf.union(current_index, pos2idx(x, y + 1));
+                // Check down neighbor
+                if y + 1 < SIDE && grid[pos2idx(x, y + 1)] == current_char {
+                    uf.union(current_index, pos2idx(x, y + 1));
+                }
             }
         }
-    }
+    );
 
     // Collect subsets (connected components)
     let mut regions: HashMap<u16, Vec<(u8, u8)>> = HashMap::default();
-    for y in 0..SIDE {
-        for x in 0..SIDE {
-            let index = pos2idx(x, y);
-            let root = uf.find(index);
-            regions.entry(root as u16).or_default().push((x as u8, y as u8));
+    aoc_trace::span!(
+        "collect_regions",
+        for y in 0..SIDE {
+            for x in 0..SIDE {
+                let index = pos2idx(x, y);
+                let root = uf.find(index);
+                regions.entry(root as u16).or_default().push((x as u8, y as u8));
+            }
         }
-    }
+    );
 
-    regions
-        .values()
-        .collect::<Vec<_>>()
-        .into_par_iter()
-        .map(|subset| {
-            let a = subset.len();
-            let mut p = 0;
-            let mut s = 0;
-
-            let set: FixedBitSet = subset.iter().map(|&(x, y)| pos2idx(x, y)).collect();
-            let in_region = |x, y| set.contains(pos2idx(x, y));
-
-            for &(x, y) in subset {
-                // Perimeter calculation
-                for &(dx, dy) in &DIRECTIONS {
-                    let nx = x as isize + dx;
-                    let ny = y as isize + dy;
-                    if !(0..SIDE as isize).contains(&nx)
-                        || !(0..SIDE as isize).contains(&ny)
-                        || !set.contains(pos2idx(nx as usize, ny as usize))
-                    {
-                        p += 1;
-                    }
-                }
+    regions.into_values().collect()
+}
 
-                // Outer corners
-                // Top-left
-                if !in_region(x.wrapping_sub(1), y) && !in_region(x, y.wrapping_sub(1)) {
-                    s += 1;
-                }
-                // Top-right
-                if !in_region(x + 1, y) && !in_region(x, y.wrapping_sub(1)) {
-                    s += 1;
-                }
-                // Bottom-left
-                if !in_region(x.wrapping_sub(1), y) && !in_region(x, y + 1) {
-                    s += 1;
-                }
-                // Bottom-right
-                if !in_region(x + 1, y) && !in_region(x, y + 1) {
-                    s += 1;
-                }
+fn cells(region: &[(u8, u8)]) -> FixedBitSet {
+    region.iter().map(|&(x, y)| pos2idx(x, y)).collect()
+}
 
-                // Inner corners
-                // Top-left
-                if in_region(x.wrapping_sub(1), y)
-                    && in_region(x, y.wrapping_sub(1))
-                    && !in_region(x.wrapping_sub(1), y.wrapping_sub(1))
-                {
-                    s += 1;
-                }
-                // Top-right
-                if in_region(x + 1, y) && in_region(x, y.wrapping_sub(1)) && !in_region(x + 1, y.wrapping_sub(1)) {
-                    s += 1;
-                }
-                // Bottom-left
-                if in_region(x.wrapping_sub(1), y) && in_region(x, y + 1) && !in_region(x.wrapping_sub(1), y + 1) {
-                    s += 1;
-                }
-                // Bottom-right
-                if in_region(x + 1, y) && in_region(x, y + 1) && !in_region(x + 1, y + 1) {
-                    s += 1;
-                }
+/// The number of edges between a cell of the region, whose cells are also in `set`, and one outside it.
+fn perimeter(region: &[(u8, u8)], set: &FixedBitSet) -> usize {
+    let mut p = 0;
+    for &(x, y) in region {
+        for &(dx, dy) in &DIRECTIONS {
+            let nx = x as isize + dx;
+            let ny = y as isize + dy;
+            if !(0..SIDE as isize).contains(&nx)
+                || !(0..SIDE as isize).contains(&ny)
+                || !set.contains(pos2idx(nx as usize, ny as usize))
+            {
+                p += 1;
             }
+        }
+    }
+    p
+}
 
-            (a * p, a * s)
-        })
-        .reduce(|| (0, 0), |(t1_acc, t2_acc), (t1, t2)| (t1_acc + t1, t2_acc + t2))
+/// The number of sides of the region, whose cells are also in `set`, counted as its corners.
+fn sides(region: &[(u8, u8)], set: &FixedBitSet) -> usize {
+    let in_region = |x, y| set.contains(pos2idx(x, y));
+
+    let mut s = 0;
+    for &(x, y) in region {
+        // Outer corners
+        // Top-left
+        if !in_region(x.wrapping_sub(1), y) && !in_region(x, y.wrapping_sub(1)) {
+            s += 1;
+        }
+        // Top-right
+        if !in_region(x + 1, y) && !in_region(x, y.wrapping_sub(1)) {
+            s += 1;
+        }
+        // Bottom-left
+        if !in_region(x.wrapping_sub(1), y) && !in_region(x, y + 1) {
+            s += 1;
+        }
+        // Bottom-right
+        if !in_region(x + 1, y) && !in_region(x, y + 1) {
+            s += 1;
+        }
+
+        // Inner corners
+        // Top-left
+        if in_region(x.wrapping_sub(1), y)
+            && in_region(x, y.wrapping_sub(1))
+            && !in_region(x.wrapping_sub(1), y.wrapping_sub(1))
+        {
+            s += 1;
+        }
+        // Top-right
+        if in_region(x + 1, y) && in_region(x, y.wrapping_sub(1)) && !in_region(x + 1, y.wrapping_sub(1)) {
+            s += 1;
+        }
+        // Bottom-left
+        if in_region(x.wrapping_sub(1), y) && in_region(x, y + 1) && !in_region(x.wrapping_sub(1), y + 1) {
+            s += 1;
+        }
+        // Bottom-right
+        if in_region(x + 1, y) && in_region(x, y + 1) && !in_region(x + 1, y + 1) {
+            s += 1;
+        }
+    }
+    s
 }
@@ -3,10 +3,25 @@ use std::fmt::Display;
 mod part1;
 mod part2;
 
+fn parse() -> Vec<part1::ClawMachine> {
+    let input = include_str!(concat!(env!("OUT_DIR"), "/input.txt"));
+    aoc_trace::span!("parse", part1::load_input(input))
+}
+
 #[inline]
 pub fn solve() -> (impl Display, impl Display) {
-    let input = include_str!("input.txt");
-    let machines = part1::load_input(input);
+    let machines = parse();
+
+    rayon::join(
+        || aoc_trace::span!("part1", part1::do_solve(&machines)),
+        || aoc_trace::span!("part2", part2::do_solve(&machines)),
+    )
+}
+
+pub fn part1() -> impl Display {
+    part1::do_solve(&parse())
+}
 
-    rayon::join(|| part1::do_solve(&machines), || part2::do_solve(&machines))
+pub fn part2() -> impl Display {
+    part2::do_solve(&parse())
 }
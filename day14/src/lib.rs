@@ -41,15 +41,27 @@ impl Robot {
     }
 }
 
+fn parse() -> Vec<Robot> {
+    let input = include_str!(concat!(env!("OUT_DIR"), "/input.txt"));
+    aoc_trace::span!("parse", input.lines().map(Robot::parse).collect())
+}
+
 #[inline]
 pub fn solve() -> (impl Display, impl Display) {
-    let input = include_str!("input.txt");
-    let robots = input.lines().map(Robot::parse).collect::<Vec<_>>();
-
+    let robots = parse();
     rayon::join(|| solve_part1(&robots), || solve_part2(&robots))
 }
 
+pub fn part1() -> impl Display {
+    solve_part1(&parse())
+}
+
+pub fn part2() -> impl Display {
+    solve_part2(&parse())
+}
+
 fn solve_part1(robots: &[Robot]) -> usize {
+    aoc_trace::span!("part1");
     robots
         .par_iter()
         .map(|r| r.advance(TIME))
@@ -81,6 +93,7 @@ fn solve_part1(robots: &[Robot]) -> usize {
 // what even is a christmas tree?
 // modular arithmetic solution based on https://www.reddit.com/r/adventofcode/comments/1hdvhvu/2024_day_14_solutions/m1zws1g/
 fn solve_part2(robots: &[Robot]) -> Coord {
+    aoc_trace::span!("part2");
     let (bx, by) = rayon::join(
         // Find the time `bx` with minimal variance in x coordinates
         || {
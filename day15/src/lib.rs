@@ -73,22 +73,38 @@ fn do_move(map: &mut [u8], (y, x): (usize, usize), width: usize, (dy, dx): (isiz
     }
 }
 
-#[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    let input = include_str!("input.txt");
+fn parse() -> (Vec<u8>, usize, &'static str) {
+    let input = include_str!(concat!(env!("OUT_DIR"), "/input.txt"));
 
     let (map, moves) = input.split_once("\n\n").unwrap();
 
     let width = map.lines().next().unwrap().len();
 
-    let map = map.bytes().filter(|&b| b != b'\n').collect::<Vec<_>>();
+    let map = aoc_trace::span!("parse", map.bytes().filter(|&b| b != b'\n').collect::<Vec<_>>());
+
+    (map, width, moves)
+}
+
+#[inline]
+pub fn solve() -> (impl Display, impl Display) {
+    let (map, width, moves) = parse();
 
     rayon::join(
-        || do_solve::<false>(map.clone(), width, moves),
-        || do_solve::<true>(map.clone(), width, moves),
+        || aoc_trace::span!("part1", do_solve::<false>(map.clone(), width, moves)),
+        || aoc_trace::span!("part2", do_solve::<true>(map.clone(), width, moves)),
     )
 }
 
+pub fn part1() -> impl Display {
+    let (map, width, moves) = parse();
+    do_solve::<false>(map, width, moves)
+}
+
+pub fn part2() -> impl Display {
+    let (map, width, moves) = parse();
+    do_solve::<true>(map, width, moves)
+}
+
 fn do_solve<const PART2: bool>(mut map: Vec<u8>, mut width: usize, moves: &str) -> usize {
     if PART2 {
         map = map
@@ -122,68 +122,113 @@ fn distance_map<const REVERSE: bool>(start: State, goal: (u8, u8), map: &FixedBi
     dist
 }
 
-// logic adapted from https://www.reddit.com/r/adventofcode/comments/1hfboft/2024_day_16_solutions/m2akf0n/
-#[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    let input = include_str!("input.txt");
-    let side = input.lines().next().unwrap().len();
+struct Maze {
+    side: usize,
+    walkable: FixedBitSet,
+    start: (u8, u8),
+    end: (u8, u8),
+}
 
-    let map = input.bytes().filter(|&b| b != b'\n').collect::<Vec<_>>();
+impl Maze {
+    fn parse(input: &str) -> Self {
+        let side = input.lines().next().unwrap().len();
 
-    let mut start = usize::MAX;
-    let mut end = usize::MAX;
-    let mut walkable = FixedBitSet::with_capacity(side * side);
+        let map = input.bytes().filter(|&b| b != b'\n').collect::<Vec<_>>();
+
+        let mut start = usize::MAX;
+        let mut end = usize::MAX;
+        let mut walkable = FixedBitSet::with_capacity(side * side);
+
+        aoc_trace::span!(
+            "parse",
+            for (i, b) in map.iter().enumerate() {
+                match b {
+                    b'S' => start = i,
+                    b'E' => end = i,
+                    b'#' => continue,
+                    _ => {}
+                }
+                walkable.insert(i);
+            }
+        );
 
-    for (i, b) in map.iter().enumerate() {
-        match b {
-            b'S' => start = i,
-            b'E' => end = i,
-            b'#' => continue,
-            _ => {}
+        Self {
+            side,
+            walkable,
+            start: ((start / side) as u8, (start % side) as u8),
+            end: ((end / side) as u8, (end % side) as u8),
         }
-        walkable.insert(i);
     }
-    let (start_y, start_x) = ((start / side) as u8, (start % side) as u8);
-    let (end_y, end_x) = ((end / side) as u8, (end % side) as u8);
-
-    let (forward_dist_by_state, reverse_dist_by_state) = rayon::join(
-        || {
-            distance_map::<false>(
-                State {
-                    pos: (start_y, start_x),
-                    dir: (0, 1),
-                },
-                (end_y, end_x),
-                &walkable,
-                side,
-            )
-        },
-        || {
-            distance_map::<true>(
-                State {
-                    pos: (end_y, end_x),
-                    dir: (-1, 0),
-                },
-                (start_y, start_x),
-                &walkable,
-                side,
-            )
-        },
-    );
-
-    let p1 = forward_dist_by_state[State {
-        pos: (end_y, end_x),
-        dir: (-1, 0),
+
+    fn forward_distance_map(&self) -> Vec<usize> {
+        aoc_trace::span!("forward_distance_map");
+        distance_map::<false>(
+            State {
+                pos: self.start,
+                dir: (0, 1),
+            },
+            self.end,
+            &self.walkable,
+            self.side,
+        )
+    }
+
+    fn reverse_distance_map(&self) -> Vec<usize> {
+        aoc_trace::span!("reverse_distance_map");
+        distance_map::<true>(
+            State {
+                pos: self.end,
+                dir: (-1, 0),
+            },
+            self.start,
+            &self.walkable,
+            self.side,
+        )
     }
-    .to_index(side)];
 
+    /// The lowest score to the end, from the forward distance map.
+    fn lowest_score(&self, forward_dist_by_state: &[usize]) -> usize {
+        forward_dist_by_state[State {
+            pos: self.end,
+            dir: (-1, 0),
+        }
+        .to_index(self.side)]
+    }
+}
+
+/// The number of tiles on any path with the lowest score, which are the states whose distances from the start and to
+/// the end add up to it.
+fn best_seats(forward_dist_by_state: Vec<usize>, reverse_dist_by_state: Vec<usize>, p1: usize, side: usize) -> usize {
+    aoc_trace::span!("best_seats");
     let mut sit_set = FixedBitSet::with_capacity(side * side);
     for ((s1_idx, d1), d2) in forward_dist_by_state.into_iter().enumerate().zip(reverse_dist_by_state) {
         if d1 != usize::MAX && d2 != usize::MAX && d1 + d2 == p1 {
             sit_set.insert(s1_idx / 4);
         }
     }
-    let p2 = sit_set.count_ones(..);
+    sit_set.count_ones(..)
+}
+
+// logic adapted from https://www.reddit.com/r/adventofcode/comments/1hfboft/2024_day_16_solutions/m2akf0n/
+#[inline]
+pub fn solve() -> (impl Display, impl Display) {
+    let maze = Maze::parse(include_str!(concat!(env!("OUT_DIR"), "/input.txt")));
+
+    let (forward_dist_by_state, reverse_dist_by_state) =
+        rayon::join(|| maze.forward_distance_map(), || maze.reverse_distance_map());
+
+    let p1 = maze.lowest_score(&forward_dist_by_state);
+    let p2 = best_seats(forward_dist_by_state, reverse_dist_by_state, p1, maze.side);
 
     (p1, p2)
 }
+
+pub fn part1() -> impl Display {
+    let maze = Maze::parse(include_str!(concat!(env!("OUT_DIR"), "/input.txt")));
+    maze.lowest_score(&maze.forward_distance_map())
+}
+
+pub fn part2() -> impl Display {
+    let (_, p2) = solve();
+    p2
+}
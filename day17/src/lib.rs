@@ -114,9 +114,8 @@ impl Computer {
     }
 }
 
-#[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    let input = include_str!("input.txt");
+fn parse() -> (Vec<u8>, Computer) {
+    let input = include_str!(concat!(env!("OUT_DIR"), "/input.txt"));
 
     let mut lines = input.lines();
 
@@ -137,12 +136,29 @@ pub fn solve() -> (impl Display, impl Display) {
         output: None,
     };
 
-    let p1 = solve_part1(&program, computer);
-    let p2 = solve_part2(&program, computer, program.len() - 1, 0).unwrap();
+    (program, computer)
+}
+
+#[inline]
+pub fn solve() -> (impl Display, impl Display) {
+    let (program, computer) = parse();
+
+    let p1 = aoc_trace::span!("part1", solve_part1(&program, computer));
+    let p2 = aoc_trace::span!("part2", solve_part2(&program, computer, program.len() - 1, 0).unwrap());
 
     (p1, p2)
 }
 
+pub fn part1() -> impl Display {
+    let (program, computer) = parse();
+    solve_part1(&program, computer)
+}
+
+pub fn part2() -> impl Display {
+    let (program, computer) = parse();
+    solve_part2(&program, computer, program.len() - 1, 0).unwrap()
+}
+
 fn solve_part1(program: &[u8], mut computer: Computer) -> String {
     std::iter::from_fn(|| computer.step_until_output(program))
         .map(|n| n.to_string())
@@ -9,21 +9,31 @@ fn pos2idx(x: u8, y: u8) -> usize {
     usize::from(y) * usize::from(SIDE) + usize::from(x)
 }
 
+/// The coordinates of the falling bytes, in order.
+fn bytes(input: &str) -> impl Iterator<Item = (u8, u8)> + '_ {
+    let mut coordinates = aoc_parse::uints(input);
+    std::iter::from_fn(move || Some((coordinates.next()?, coordinates.next()?)))
+}
+
 #[inline]
 pub fn solve() -> (impl Display, impl Display) {
-    let input = include_str!("input.txt");
+    let input = include_str!(concat!(env!("OUT_DIR"), "/input.txt"));
     rayon::join(|| solve_part1(input), || solve_part2(input))
 }
 
+pub fn part1() -> impl Display {
+    solve_part1(include_str!(concat!(env!("OUT_DIR"), "/input.txt")))
+}
+
+pub fn part2() -> impl Display {
+    solve_part2(include_str!(concat!(env!("OUT_DIR"), "/input.txt")))
+}
+
 fn solve_part1(input: &str) -> u16 {
+    aoc_trace::span!("part1");
     let mut walls = FixedBitSet::with_capacity(MAP_SIZE);
 
-    input.lines().take(1024).for_each(|line| {
-        let (x, y) = line.split_once(',').unwrap();
-        let x = x.parse::<u8>().unwrap();
-        let y = y.parse::<u8>().unwrap();
-        walls.insert(pos2idx(x, y));
-    });
+    bytes(input).take(1024).for_each(|(x, y)| walls.insert(pos2idx(x, y)));
 
     pathfinding::prelude::astar(
         &(0u8, 0u8),
@@ -49,15 +59,8 @@ fn solve_part1(input: &str) -> u16 {
 // code inspired by
 // https://www.reddit.com/r/adventofcode/comments/1hgv0mt/2024_day_18_part_2_if_it_aint_broke_dont_fix_it/m2mmfx7/:walls_in_order
 fn solve_part2(input: &str) -> &str {
-    let mut walls_in_order = input
-        .lines()
-        .map(|line| {
-            let (x, y) = line.split_once(',').unwrap();
-            let x = x.parse::<u8>().unwrap();
-            let y = y.parse::<u8>().unwrap();
-            (x, y)
-        })
-        .collect::<Vec<_>>();
+    aoc_trace::span!("part2");
+    let mut walls_in_order = bytes(input).collect::<Vec<_>>();
 
     let mut walls = FixedBitSet::with_capacity(MAP_SIZE);
     walls_in_order.iter().for_each(|&(x, y)| walls.insert(pos2idx(x, y)));
@@ -17,17 +17,37 @@ fn can_make(tiles: &'static [&'static [u8]], pattern: &'static [u8]) -> usize {
         .sum()
 }
 
-#[inline]
-pub fn solve() -> (impl Display, impl Display) {
+/// The number of ways to make each pattern from the towels.
+fn arrangements() -> impl ParallelIterator<Item = usize> {
     memoized_flush_can_make();
-    let input = include_str!("input.txt");
+    let input = include_str!(concat!(env!("OUT_DIR"), "/input.txt"));
 
     let (tiles, patterns) = input.split_once("\n\n").unwrap();
     let tiles = tiles.split(", ").map(|t| t.as_bytes()).collect::<Vec<_>>().leak();
 
-    patterns
-        .par_lines()
-        .map(|p| can_make(&*tiles, p.as_bytes()))
+    patterns.par_lines().map(|p| can_make(&*tiles, p.as_bytes()))
+}
+
+#[inline]
+pub fn solve() -> (impl Display, impl Display) {
+    let arrangements = arrangements();
+
+    aoc_trace::span!("arrange");
+    arrangements
         .map(|n| (if n > 0 { 1 } else { 0 }, n))
         .reduce(|| (0, 0), |(a1, a2), (b1, b2)| (a1 + b1, a2 + b2))
 }
+
+pub fn part1() -> impl Display {
+    let arrangements = arrangements();
+
+    aoc_trace::span!("arrange");
+    arrangements.filter(|&n| n > 0).count()
+}
+
+pub fn part2() -> impl Display {
+    let arrangements = arrangements();
+
+    aoc_trace::span!("arrange");
+    arrangements.sum::<usize>()
+}
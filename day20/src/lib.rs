@@ -15,34 +15,55 @@ fn neighbors((y, x): Point) -> [Point; 4] {
     ]
 }
 
-#[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    let input = include_str!("input.txt");
+fn parse() -> (u8, FixedBitSet, Point) {
+    let input = include_str!(concat!(env!("OUT_DIR"), "/input.txt"));
     let side = u8::try_from(input.lines().next().unwrap().len()).unwrap();
 
     let mut end = (u8::MAX, u8::MAX);
     let mut walls = FixedBitSet::with_capacity(usize::from(side) * usize::from(side));
 
-    for (y, row) in input.lines().enumerate() {
-        for (x, cell) in row.bytes().enumerate() {
-            match cell {
-                b'E' => end = (y as u8, x as u8),
-                b'#' => walls.insert(y * usize::from(side) + x),
-                b'S' | b'.' | b'\n' => (),
-                _ => unreachable!("{cell:?}"),
+    aoc_trace::span!(
+        "parse",
+        for (y, row) in input.lines().enumerate() {
+            for (x, cell) in row.bytes().enumerate() {
+                match cell {
+                    b'E' => end = (y as u8, x as u8),
+                    b'#' => walls.insert(y * usize::from(side) + x),
+                    b'S' | b'.' | b'\n' => (),
+                    _ => unreachable!("{cell:?}"),
+                }
             }
         }
-    }
+    );
+
+    (side, walls, end)
+}
+
+#[inline]
+pub fn solve() -> (impl Display, impl Display) {
+    let (side, walls, end) = parse();
 
     rayon::join(
-        || do_solve::<2>(side, &walls, end),
-        || do_solve::<20>(side, &walls, end),
+        || aoc_trace::span!("part1", do_solve::<2>(side, &walls, end)),
+        || aoc_trace::span!("part2", do_solve::<20>(side, &walls, end)),
     )
 }
 
+pub fn part1() -> impl Display {
+    let (side, walls, end) = parse();
+    do_solve::<2>(side, &walls, end)
+}
+
+pub fn part2() -> impl Display {
+    let (side, walls, end) = parse();
+    do_solve::<20>(side, &walls, end)
+}
+
 fn do_solve<const STEPS: i8>(side: u8, walls: &FixedBitSet, end: (u8, u8)) -> usize {
     // Assumption: there's only one path from the start to the end
-    let end_dist_map = compute_dist_map(side, end, walls);
+    let end_dist_map = aoc_trace::span!("distance_map", compute_dist_map(side, end, walls));
+
+    aoc_trace::span!("cheats");
 
     (0..side)
         .into_par_iter()
@@ -97,18 +97,35 @@ fn transform(keypad: Keypad, code: Vec<u8>) -> Vec<u8> {
     moves
 }
 
-#[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    // for benchmarking
+fn flush() {
     memoized_flush_transform();
     memoized_flush_calculate_move();
     memoized_flush_find_keypad_position();
+}
+
+#[inline]
+pub fn solve() -> (impl Display, impl Display) {
+    // for benchmarking
+    flush();
+
+    rayon::join(
+        || aoc_trace::span!("part1", do_solve(2)),
+        || aoc_trace::span!("part2", do_solve(25)),
+    )
+}
+
+pub fn part1() -> impl Display {
+    flush();
+    do_solve(2)
+}
 
-    rayon::join(|| do_solve(2), || do_solve(25))
+pub fn part2() -> impl Display {
+    flush();
+    do_solve(25)
 }
 
 fn do_solve(k: usize) -> usize {
-    include_str!("input.txt")
+    include_str!(concat!(env!("OUT_DIR"), "/input.txt"))
         .par_lines()
         .map(|line| complexity(line.as_bytes(), k))
         .sum::<usize>()
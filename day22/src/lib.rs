@@ -49,50 +49,76 @@ impl ChunkResult {
 
 #[inline]
 pub fn solve() -> (impl Display, impl Display) {
-    // Collect all lines first
-    let lines: Vec<_> = include_str!("input.txt").lines().collect();
+    solve_input(include_str!(concat!(env!("OUT_DIR"), "/input.txt")))
+}
 
-    // Process in chunks
-    let results = lines
-        .par_chunks(CHUNK_SIZE)
-        .map(|chunk| {
-            let mut chunk_result = ChunkResult::new();
-
-            for line in chunk {
-                let n = line.parse::<u64>().unwrap();
-                let values: Vec<_> = generator(n).take(2001).collect();
-
-                // Part 1
-                chunk_result.sum += values[values.len() - 1];
-
-                // Part 2
-                let deltas: Vec<_> = values
-                    .iter()
-                    .skip(1)
-                    .scan(values[0], |prev, &x| {
-                        let diff = x as i64 % 10 - *prev as i64 % 10;
-                        *prev = x;
-                        Some(diff as i8)
-                    })
-                    .collect();
-
-                let mut seen = FixedBitSet::with_capacity(SIZE);
-                deltas.windows(4).enumerate().for_each(|(i, w)| {
-                    let idx = to_index(w);
-                    if !seen.contains(idx) {
-                        seen.insert(idx);
-                        let price = (values[i + 4] % 10) as u16;
-                        chunk_result.scores[idx] += price;
-                    }
-                });
-            }
-
-            chunk_result
-        })
-        .reduce(ChunkResult::new, |a, b| a.merge(b));
+pub fn solve_input(input: &str) -> (impl Display, impl Display) {
+    let results = simulate(&parse(input));
 
     let p1 = results.sum;
-    let p2 = results.scores.into_iter().max().unwrap();
+    let p2 = aoc_trace::span!("best_sequence", results.scores.into_iter().max().unwrap());
 
     (p1, p2)
 }
+
+pub fn part1() -> impl Display {
+    let seeds = parse(include_str!(concat!(env!("OUT_DIR"), "/input.txt")));
+
+    aoc_trace::span!("simulate");
+    seeds
+        .par_iter()
+        .map(|&seed| generator(seed).nth(2000).unwrap())
+        .sum::<u64>()
+}
+
+pub fn part2() -> impl Display {
+    let results = simulate(&parse(include_str!(concat!(env!("OUT_DIR"), "/input.txt"))));
+    aoc_trace::span!("best_sequence", results.scores.into_iter().max().unwrap())
+}
+
+fn parse(input: &str) -> Vec<u64> {
+    aoc_trace::span!("parse", aoc_parse::uints::<u64>(input).collect())
+}
+
+fn simulate(seeds: &[u64]) -> ChunkResult {
+    // Process in chunks
+    aoc_trace::span!(
+        "simulate",
+        seeds
+            .par_chunks(CHUNK_SIZE)
+            .map(|chunk| {
+                let mut chunk_result = ChunkResult::new();
+
+                for &seed in chunk {
+                    let values: Vec<_> = generator(seed).take(2001).collect();
+
+                    // Part 1
+                    chunk_result.sum += values[values.len() - 1];
+
+                    // Part 2
+                    let deltas: Vec<_> = values
+                        .iter()
+                        .skip(1)
+                        .scan(values[0], |prev, &x| {
+                            let diff = x as i64 % 10 - *prev as i64 % 10;
+                            *prev = x;
+                            Some(diff as i8)
+                        })
+                        .collect();
+
+                    let mut seen = FixedBitSet::with_capacity(SIZE);
+                    deltas.windows(4).enumerate().for_each(|(i, w)| {
+                        let idx = to_index(w);
+                        if !seen.contains(idx) {
+                            seen.insert(idx);
+                            let price = (values[i + 4] % 10) as u16;
+                            chunk_result.scores[idx] += price;
+                        }
+                    });
+                }
+
+                chunk_result
+            })
+            .reduce(ChunkResult::new, |a, b| a.merge(b))
+    )
+}
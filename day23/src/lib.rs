@@ -3,25 +3,44 @@ use std::fmt::Display;
 use petgraph::prelude::*;
 use rayon::prelude::*;
 
-type Graph = UnGraph<&'static str, ()>;
+type Graph<'a> = UnGraph<&'a str, ()>;
+
+fn parse(input: &str) -> Graph<'_> {
+    aoc_trace::span!(
+        "parse",
+        UnGraphMap::<&str, ()>::from_edges(input.lines().map(|line| line.split_once('-').unwrap())).into_graph()
+    )
+}
 
 #[inline]
 pub fn solve() -> (impl Display, impl Display) {
-    let input = include_str!("input.txt");
+    solve_input(include_str!(concat!(env!("OUT_DIR"), "/input.txt")))
+}
+
+pub fn solve_input(input: &str) -> (impl Display, impl Display) {
+    let graph = parse(input);
 
-    let graph = UnGraphMap::<&str, ()>::from_edges(input.lines().map(|line| line.split_once('-').unwrap()));
-    let graph = graph.into_graph();
+    rayon::join(
+        || aoc_trace::span!("part1", solve_part1(&graph)),
+        || aoc_trace::span!("part2", solve_part2(&graph)),
+    )
+}
+
+pub fn part1() -> impl Display {
+    solve_part1(&parse(include_str!(concat!(env!("OUT_DIR"), "/input.txt"))))
+}
 
-    rayon::join(|| solve_part1(&graph), || solve_part2(&graph))
+pub fn part2() -> impl Display {
+    solve_part2(&parse(include_str!(concat!(env!("OUT_DIR"), "/input.txt"))))
 }
 
-fn triangles(graph: &Graph) -> impl Iterator<Item = [NodeIndex; 3]> + '_ {
+fn triangles<'a>(graph: &'a Graph) -> impl Iterator<Item = [NodeIndex; 3]> + 'a {
     graph
         .node_indices()
         .flat_map(move |node| triangles_starting_from(graph, node))
 }
 
-fn triangles_starting_from(graph: &Graph, node: NodeIndex) -> impl Iterator<Item = [NodeIndex; 3]> + '_ {
+fn triangles_starting_from<'a>(graph: &'a Graph, node: NodeIndex) -> impl Iterator<Item = [NodeIndex; 3]> + 'a {
     graph.neighbors(node).enumerate().flat_map(move |(i, alice)| {
         graph
             .neighbors(node)
@@ -74,35 +74,55 @@ fn eval_num(gates: &Gates, prefix: u8) -> u64 {
         .fold(0u64, |acc, x| (acc << 1) | if x { 1 } else { 0 })
 }
 
+fn parse() -> Gates {
+    let input = include_str!(concat!(env!("OUT_DIR"), "/input.txt"));
+    let mut gates = HashMap::new();
+    aoc_trace::span!(
+        "parse",
+        input.lines().for_each(|line| {
+            if line.contains(":") {
+                let (dst, val) = line.split_once(": ").unwrap();
+                let dst = dst.as_bytes().try_into().unwrap();
+                gates.insert(dst, Gate::Constant(val == "1"));
+            } else if line.contains(" -> ") {
+                let (operands, dst) = line.split_once(" -> ").unwrap();
+                let dst = dst.as_bytes().try_into().unwrap();
+                let mut it = operands.split(' ');
+                let lhs = it.next().unwrap().as_bytes().try_into().unwrap();
+                let op = it.next().unwrap();
+                let rhs = it.next().unwrap().as_bytes().try_into().unwrap();
+
+                gates.insert(
+                    dst,
+                    match op {
+                        "AND" => Gate::And(lhs, rhs),
+                        "OR" => Gate::Or(lhs, rhs),
+                        "XOR" => Gate::Xor(lhs, rhs),
+                        _ => panic!("Unknown operator: {}", op),
+                    },
+                );
+            }
+        })
+    );
+    gates
+}
+
 #[inline]
 pub fn solve() -> (impl Display, impl Display) {
-    let mut gates = HashMap::new();
-    include_str!("input.txt").lines().for_each(|line| {
-        if line.contains(":") {
-            let (dst, val) = line.split_once(": ").unwrap();
-            let dst = dst.as_bytes().try_into().unwrap();
-            gates.insert(dst, Gate::Constant(val == "1"));
-        } else if line.contains(" -> ") {
-            let (operands, dst) = line.split_once(" -> ").unwrap();
-            let dst = dst.as_bytes().try_into().unwrap();
-            let mut it = operands.split(' ');
-            let lhs = it.next().unwrap().as_bytes().try_into().unwrap();
-            let op = it.next().unwrap();
-            let rhs = it.next().unwrap().as_bytes().try_into().unwrap();
-
-            gates.insert(
-                dst,
-                match op {
-                    "AND" => Gate::And(lhs, rhs),
-                    "OR" => Gate::Or(lhs, rhs),
-                    "XOR" => Gate::Xor(lhs, rhs),
-                    _ => panic!("Unknown operator: {}", op),
-                },
-            );
-        }
-    });
+    let gates = parse();
+
+    rayon::join(
+        || aoc_trace::span!("part1", eval_num(&gates, b'z')),
+        || aoc_trace::span!("part2", find_swaps(&gates)),
+    )
+}
+
+pub fn part1() -> impl Display {
+    eval_num(&parse(), b'z')
+}
 
-    rayon::join(|| eval_num(&gates, b'z'), || find_swaps(&gates))
+pub fn part2() -> impl Display {
+    find_swaps(&parse())
 }
 
 // Originally I solved this, as can be seen in commit abede62, by manually building the "expected" forms of the gates,
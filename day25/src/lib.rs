@@ -46,23 +46,33 @@ fn fit_together(key: [u8; 5], door: [u8; 5]) -> bool {
 
 #[inline]
 pub fn solve() -> (impl Display, impl Display) {
-    let schematics = include_str!("input.txt")
+    (part1(), part2())
+}
+
+pub fn part1() -> impl Display {
+    let schematics = include_str!(concat!(env!("OUT_DIR"), "/input.txt"))
         .split("\n\n")
         .map(Schematic::parse);
 
     let mut keys = Vec::new();
     let mut doors = Vec::new();
-    for schematic in schematics {
-        match schematic.ty {
-            SchematicType::Key => keys.push(schematic.columns),
-            SchematicType::Door => doors.push(schematic.columns),
+    aoc_trace::span!(
+        "parse",
+        for schematic in schematics {
+            match schematic.ty {
+                SchematicType::Key => keys.push(schematic.columns),
+                SchematicType::Door => doors.push(schematic.columns),
+            }
         }
-    }
+    );
 
-    let p1 = keys
-        .par_iter()
+    aoc_trace::span!("fit");
+    keys.par_iter()
         .map(|&key| doors.iter().filter(|&&door| fit_together(key, door)).count())
-        .sum::<usize>();
+        .sum::<usize>()
+}
 
-    (p1, "Merry Christmas!")
+/// There's no puzzle for part 2 on the last day.
+pub fn part2() -> impl Display {
+    "Merry Christmas!"
 }
@@ -0,0 +1,182 @@
+//! Puzzle inputs can't be stored in the repo in the clear, so they're stored encrypted with ChaCha20-Poly1305 and
+//! decrypted by each day's build script into `OUT_DIR`.
+use std::{
+    env, fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    ChaCha20Poly1305, Key, Nonce,
+};
+
+/// Environment variable holding the hex-encoded key; takes precedence over the key file.
+pub const KEY_VAR: &str = "AOC_INPUT_KEY";
+
+/// Name of the key file, relative to the workspace root.
+pub const KEY_FILE: &str = ".aoc-input-key";
+
+pub const PLAIN_NAME: &str = "input.txt";
+pub const ENCRYPTED_NAME: &str = "input.txt.enc";
+
+const NONCE_LEN: usize = 12;
+
+#[derive(Debug)]
+pub enum Error {
+    MissingKey,
+    MalformedKey,
+    Corrupt,
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::MissingKey => write!(
+                f,
+                "no input key found: set {KEY_VAR} or create {}",
+                key_path().display()
+            ),
+            Error::MalformedKey => write!(f, "the input key must be 64 hex digits"),
+            Error::Corrupt => write!(f, "decryption failed: wrong key or corrupted input"),
+            Error::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+pub fn workspace_root() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap()
+}
+
+pub fn key_path() -> PathBuf {
+    workspace_root().join(KEY_FILE)
+}
+
+pub fn generate_key() -> Key {
+    ChaCha20Poly1305::generate_key(&mut OsRng)
+}
+
+pub fn parse_key(s: &str) -> Result<Key, Error> {
+    let mut key = Key::default();
+    hex::decode_to_slice(s.trim(), &mut key).map_err(|_| Error::MalformedKey)?;
+    Ok(key)
+}
+
+pub fn format_key(key: &Key) -> String {
+    hex::encode(key)
+}
+
+/// Load the key from the environment, falling back to the key file.
+pub fn load_key() -> Result<Key, Error> {
+    match env::var(KEY_VAR) {
+        Ok(s) => parse_key(&s),
+        Err(_) => match fs::read_to_string(key_path()) {
+            Ok(s) => parse_key(&s),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Err(Error::MissingKey),
+            Err(e) => Err(e.into()),
+        },
+    }
+}
+
+/// Encrypt `plaintext`, prepending the randomly generated nonce to the ciphertext.
+pub fn encrypt(key: &Key, plaintext: &[u8]) -> Vec<u8> {
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = ChaCha20Poly1305::new(key).encrypt(&nonce, plaintext).unwrap();
+    let mut out = nonce.to_vec();
+    out.extend(ciphertext);
+    out
+}
+
+pub fn decrypt(key: &Key, data: &[u8]) -> Result<Vec<u8>, Error> {
+    if data.len() < NONCE_LEN {
+        return Err(Error::Corrupt);
+    }
+    let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+    ChaCha20Poly1305::new(key)
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| Error::Corrupt)
+}
+
+/// Read the input of the day crate at `day`.
+///
+/// A plaintext `src/input.txt` is used as-is if present, so that freshly fetched inputs work before being encrypted;
+/// otherwise `src/input.txt.enc` is decrypted with the key from [`load_key`].
+pub fn read_input(day: &Path) -> Result<Vec<u8>, Error> {
+    let src = day.join("src");
+    match fs::read(src.join(PLAIN_NAME)) {
+        Ok(input) => Ok(input),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => decrypt(&load_key()?, &fs::read(src.join(ENCRYPTED_NAME))?),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Entry point for a day's build script: writes the day's input to `$OUT_DIR/input.txt`.
+pub fn build() {
+    let day = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").unwrap());
+    let out = PathBuf::from(env::var_os("OUT_DIR").unwrap()).join(PLAIN_NAME);
+
+    println!("cargo:rerun-if-changed={}", day.join("src").display());
+    println!("cargo:rerun-if-env-changed={KEY_VAR}");
+    if key_path().exists() {
+        println!("cargo:rerun-if-changed={}", key_path().display());
+    }
+
+    let input = read_input(&day).unwrap_or_else(|e| panic!("could not read the input of {}: {e}", day.display()));
+    fs::write(out, input).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &[u8] = b"3   4\n4   3\n2   5\n1   3\n3   9\n3   3\n";
+
+    #[test]
+    fn test_roundtrip() {
+        let key = generate_key();
+        let encrypted = encrypt(&key, INPUT);
+        assert_ne!(&encrypted[NONCE_LEN..], INPUT);
+        assert_eq!(decrypt(&key, &encrypted).unwrap(), INPUT);
+    }
+
+    #[test]
+    fn test_wrong_key() {
+        let encrypted = encrypt(&generate_key(), INPUT);
+        assert!(matches!(decrypt(&generate_key(), &encrypted), Err(Error::Corrupt)));
+    }
+
+    #[test]
+    fn test_tampered() {
+        let key = generate_key();
+        let mut encrypted = encrypt(&key, INPUT);
+        *encrypted.last_mut().unwrap() ^= 1;
+        assert!(matches!(decrypt(&key, &encrypted), Err(Error::Corrupt)));
+        assert!(matches!(decrypt(&key, &encrypted[..4]), Err(Error::Corrupt)));
+    }
+
+    #[test]
+    fn test_rekey() {
+        let old = generate_key();
+        let new = generate_key();
+        let encrypted = encrypt(&old, INPUT);
+        let rekeyed = encrypt(&new, &decrypt(&old, &encrypted).unwrap());
+        assert!(decrypt(&old, &rekeyed).is_err());
+        assert_eq!(decrypt(&new, &rekeyed).unwrap(), INPUT);
+    }
+
+    #[test]
+    fn test_key_format() {
+        let key = generate_key();
+        assert_eq!(parse_key(&format!("{}\n", format_key(&key))).unwrap(), key);
+        assert!(matches!(parse_key("deadbeef"), Err(Error::MalformedKey)));
+        assert!(matches!(parse_key(&"zz".repeat(32)), Err(Error::MalformedKey)));
+    }
+}
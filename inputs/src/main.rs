@@ -0,0 +1,112 @@
+use std::{env, fs, path::PathBuf, process::ExitCode};
+
+use aoc_inputs::*;
+
+const USAGE: &str = "\
+usage: aoc-inputs <command> [days...]
+
+commands:
+    keygen          write a new random key to the key file
+    encrypt [days]  encrypt src/input.txt into src/input.txt.enc
+    decrypt [days]  decrypt src/input.txt.enc into src/input.txt
+    rekey           re-encrypt every input under a new key and replace the key file";
+
+/// The given day directories, or all of them if none were given.
+fn days(args: &[String]) -> Vec<PathBuf> {
+    let root = workspace_root();
+    if !args.is_empty() {
+        return args.iter().map(|day| root.join(day)).collect();
+    }
+
+    let mut days = fs::read_dir(root)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.is_dir() && path.file_name().unwrap().to_string_lossy().starts_with("day"))
+        .collect::<Vec<_>>();
+    days.sort_unstable();
+    days
+}
+
+fn keygen() -> Result<(), Error> {
+    let path = key_path();
+    if path.exists() {
+        eprintln!("{} already exists, use `rekey` to replace it.", path.display());
+        return Ok(());
+    }
+    fs::write(&path, format_key(&generate_key()))?;
+    println!("Wrote a new key to {}.", path.display());
+    Ok(())
+}
+
+fn encrypt_days(args: &[String]) -> Result<(), Error> {
+    let key = load_key()?;
+    for day in days(args) {
+        let src = day.join("src");
+        let Ok(plaintext) = fs::read(src.join(PLAIN_NAME)) else {
+            continue;
+        };
+        fs::write(src.join(ENCRYPTED_NAME), encrypt(&key, &plaintext))?;
+        println!("Encrypted {}.", day.display());
+    }
+    Ok(())
+}
+
+fn decrypt_days(args: &[String]) -> Result<(), Error> {
+    let key = load_key()?;
+    for day in days(args) {
+        let src = day.join("src");
+        let Ok(data) = fs::read(src.join(ENCRYPTED_NAME)) else {
+            continue;
+        };
+        fs::write(src.join(PLAIN_NAME), decrypt(&key, &data)?)?;
+        println!("Decrypted {}.", day.display());
+    }
+    Ok(())
+}
+
+fn rekey() -> Result<(), Error> {
+    let old = load_key()?;
+    let new = generate_key();
+
+    // Decrypt everything up front, so that a bad input doesn't leave the store encrypted under two different keys.
+    let inputs = days(&[])
+        .into_iter()
+        .map(|day| day.join("src").join(ENCRYPTED_NAME))
+        .filter(|path| path.exists())
+        .map(|path| Ok((decrypt(&old, &fs::read(&path)?)?, path)))
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    for (plaintext, path) in inputs {
+        fs::write(&path, encrypt(&new, &plaintext))?;
+        println!("Re-encrypted {}.", path.display());
+    }
+
+    fs::write(key_path(), format_key(&new))?;
+    println!("Wrote the new key to {}.", key_path().display());
+    if env::var_os(KEY_VAR).is_some() {
+        println!("{KEY_VAR} is set and takes precedence over the key file, update it to the new key.");
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let result = match args.split_first() {
+        Some((cmd, [])) if cmd == "keygen" => keygen(),
+        Some((cmd, rest)) if cmd == "encrypt" => encrypt_days(rest),
+        Some((cmd, rest)) if cmd == "decrypt" => decrypt_days(rest),
+        Some((cmd, [])) if cmd == "rekey" => rekey(),
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
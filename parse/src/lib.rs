@@ -0,0 +1,253 @@
+//! Fast parsing of the lists of unsigned integers that many inputs boil down to, straight from the bytes.
+use std::marker::PhantomData;
+
+/// Lines processed per iteration of the fixed-width fast path.
+const LINES: usize = 4;
+
+/// Widest number the fixed-width fast path decodes, in digits.
+const MAX_WIDTH: usize = 16;
+
+/// Parse a run of ASCII digits, or `None` if it is empty, has anything else in it or doesn't fit in a `T`.
+pub fn uint<T: TryFrom<u64>>(digits: &[u8]) -> Option<T> {
+    if digits.is_empty() {
+        return None;
+    }
+    let n = digits.iter().try_fold(0u64, |n, &d| {
+        d.is_ascii_digit()
+            .then(|| n.checked_mul(10)?.checked_add(u64::from(d - b'0')))
+            .flatten()
+    })?;
+    T::try_from(n).ok()
+}
+
+/// Iterator over the unsigned integers in some input, skipping whatever separates them (including any `-` sign).
+///
+/// Panics on a number that doesn't fit in a `T`.
+pub struct Uints<'a, T> {
+    bytes: &'a [u8],
+    _marker: PhantomData<T>,
+}
+
+pub fn uints<T: TryFrom<u64>>(input: &str) -> Uints<'_, T> {
+    Uints {
+        bytes: input.as_bytes(),
+        _marker: PhantomData,
+    }
+}
+
+impl<T: TryFrom<u64>> Iterator for Uints<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let start = self.bytes.iter().position(u8::is_ascii_digit)?;
+        let digits = &self.bytes[start..];
+        let len = digits.iter().position(|b| !b.is_ascii_digit()).unwrap_or(digits.len());
+        self.bytes = &digits[len..];
+        Some(uint(&digits[..len]).expect("number out of range"))
+    }
+}
+
+/// Parse two whitespace-separated columns of unsigned integers, one pair per line, or `None` if there is a line that
+/// isn't such a pair or a number that doesn't fit in a `T`.
+///
+/// When every line is laid out like the first one, as generated inputs are, the numbers are decoded up to eight digits
+/// at a time, several lines per iteration; otherwise the lines are scanned byte by byte.
+pub fn columns<T: TryFrom<u64>>(input: &str) -> Option<(Vec<T>, Vec<T>)> {
+    let bytes = input.as_bytes();
+    match Layout::of(bytes) {
+        Some(layout) => layout.parse(bytes).or_else(|| columns_scalar(bytes)),
+        None => columns_scalar(bytes),
+    }
+}
+
+fn columns_scalar<T: TryFrom<u64>>(bytes: &[u8]) -> Option<(Vec<T>, Vec<T>)> {
+    let mut left = Vec::new();
+    let mut right = Vec::new();
+    for line in bytes.split(|&b| b == b'\n') {
+        let mut numbers = line.split(u8::is_ascii_whitespace).filter(|s| !s.is_empty());
+        let Some(a) = numbers.next() else {
+            continue;
+        };
+        left.push(uint(a)?);
+        right.push(uint(numbers.next()?)?);
+        if numbers.next().is_some() {
+            return None;
+        }
+    }
+    Some((left, right))
+}
+
+/// Widths of the fields of a line like `12345   67890\n`.
+struct Layout {
+    left: usize,
+    gap: usize,
+    right: usize,
+}
+
+impl Layout {
+    /// The layout of the first line, if the fast path can handle it.
+    fn of(bytes: &[u8]) -> Option<Self> {
+        let left = bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+        let gap = bytes[left..].iter().take_while(|&&b| b == b' ').count();
+        let right = bytes[left + gap..].iter().take_while(|b| b.is_ascii_digit()).count();
+        let layout = Self { left, gap, right };
+        ((1..=MAX_WIDTH).contains(&left)
+            && gap != 0
+            && (1..=MAX_WIDTH).contains(&right)
+            && bytes.get(layout.stride() - 1) == Some(&b'\n'))
+        .then_some(layout)
+    }
+
+    fn stride(&self) -> usize {
+        self.left + self.gap + self.right + 1
+    }
+
+    /// Parse the lines assuming they all have this layout, or `None` if one doesn't.
+    fn parse<T: TryFrom<u64>>(&self, bytes: &[u8]) -> Option<(Vec<T>, Vec<T>)> {
+        let stride = self.stride();
+        let lines = bytes.len() / stride;
+        let mut left = Vec::with_capacity(lines + 1);
+        let mut right = Vec::with_capacity(lines + 1);
+
+        // Invalid bytes are accumulated rather than bailed out on, to keep the loop free of branches.
+        let mut invalid = 0;
+        let mut line = |start: usize| -> Option<()> {
+            let (a, a_invalid) = decode(bytes, start + self.left, self.left);
+            let (b, b_invalid) = decode(bytes, start + stride - 1, self.right);
+            let gap = &bytes[start + self.left..start + self.left + self.gap];
+            invalid |= a_invalid | b_invalid | u64::from(bytes[start + stride - 1] != b'\n');
+            invalid |= gap.iter().fold(0, |acc, &b| acc | u64::from(b != b' '));
+            left.push(T::try_from(a).ok()?);
+            right.push(T::try_from(b).ok()?);
+            Some(())
+        };
+
+        let mut start = 0;
+        while start + LINES * stride <= lines * stride {
+            for i in 0..LINES {
+                line(start + i * stride)?;
+            }
+            start += LINES * stride;
+        }
+        while start < lines * stride {
+            line(start)?;
+            start += stride;
+        }
+        if invalid != 0 {
+            return None;
+        }
+
+        // The last line may be missing its newline, or be laid out differently.
+        let (tail_left, tail_right) = columns_scalar::<T>(&bytes[start..])?;
+        left.extend(tail_left);
+        right.extend(tail_right);
+        Some((left, right))
+    }
+}
+
+/// Decode the `width` digits ending at `end`, along with a non-zero value if any of them isn't a digit.
+fn decode(bytes: &[u8], end: usize, width: usize) -> (u64, u64) {
+    if width <= 8 {
+        decode8(load(bytes, end), width)
+    } else {
+        let (high, high_invalid) = decode8(load(bytes, end - 8), width - 8);
+        let (low, low_invalid) = decode8(load(bytes, end), 8);
+        (high * 100_000_000 + low, high_invalid | low_invalid)
+    }
+}
+
+/// The eight bytes ending at `end`, zero-padded at the front near the start of the input.
+fn load(bytes: &[u8], end: usize) -> u64 {
+    match end.checked_sub(8) {
+        Some(start) => u64::from_le_bytes(bytes[start..end].try_into().unwrap()),
+        None => {
+            let mut buf = [0; 8];
+            buf[8 - end..].copy_from_slice(&bytes[..end]);
+            u64::from_le_bytes(buf)
+        }
+    }
+}
+
+/// Decode the last `width` bytes of `v`, the first of which is the most significant digit, by combining neighbouring
+/// digits into pairs, then quads, then all eight; the leading bytes are masked off so they act as zeros.
+fn decode8(v: u64, width: usize) -> (u64, u64) {
+    let mask = u64::MAX << (8 * (8 - width));
+    // A byte is a digit if its high nibble is 3 and its low nibble doesn't carry into the high one when 6 is added.
+    let invalid = ((v & 0xf0f0_f0f0_f0f0_f0f0) ^ 0x3030_3030_3030_3030
+        | ((v & 0x0f0f_0f0f_0f0f_0f0f) + 0x0606_0606_0606_0606) & 0x1010_1010_1010_1010)
+        & mask;
+
+    let mut v = v & 0x0f0f_0f0f_0f0f_0f0f & mask;
+    v = (v * 10 + (v >> 8)) & 0x00ff_00ff_00ff_00ff;
+    v = (v * 100 + (v >> 16)) & 0x0000_ffff_0000_ffff;
+    v = (v * 10_000 + (v >> 32)) & 0x0000_0000_ffff_ffff;
+    (v, invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Lines of two numbers each with the given digit counts and gap, formatted with leading zeros.
+    fn fixed(widths: (usize, usize), gap: usize, lines: u64) -> (String, Vec<u64>, Vec<u64>) {
+        let mut input = String::new();
+        let (mut left, mut right) = (Vec::new(), Vec::new());
+        for i in 0..lines {
+            let a = (i * 7_919_393 + 13) % 10u64.pow(widths.0 as u32);
+            let b = (i * 104_729 + 977) % 10u64.pow(widths.1 as u32);
+            input += &format!("{a:0w1$}{:gap$}{b:0w2$}\n", "", w1 = widths.0, w2 = widths.1);
+            left.push(a);
+            right.push(b);
+        }
+        (input, left, right)
+    }
+
+    #[test]
+    fn test_fixed_width() {
+        for w1 in 1..=MAX_WIDTH {
+            for (w2, gap) in [(w1, 3), (MAX_WIDTH + 1 - w1, 1)] {
+                for lines in [1, 3, 4, 9] {
+                    let (input, left, right) = fixed((w1, w2), gap, lines);
+                    let layout = Layout::of(input.as_bytes()).unwrap();
+                    assert_eq!(
+                        layout.parse::<u64>(input.as_bytes()),
+                        Some((left.clone(), right.clone())),
+                        "{input:?}"
+                    );
+                    assert_eq!(columns::<u64>(&input), Some((left.clone(), right.clone())), "{input:?}");
+                    let trimmed = input.trim_end();
+                    assert_eq!(columns::<u64>(trimmed), Some((left, right)), "{trimmed:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_irregular() {
+        let input = "3   4\n4   3\n2   5\n1   3\n3   9\n3   3\n";
+        let expected = Some((vec![3u32, 4, 2, 1, 3, 3], vec![4, 3, 5, 3, 9, 3]));
+        assert_eq!(columns(input), expected);
+        assert_eq!(columns("3   4\n4  3\n2   5\n1\t3\r\n3   9\n\n3   3"), expected);
+        assert_eq!(columns("12   4\n1   34\n"), Some((vec![12u32, 1], vec![4, 34])));
+        assert_eq!(columns("12   34\n1a   34\n"), None::<(Vec<u32>, Vec<u32>)>);
+        assert_eq!(columns("12   34\n12   34   56\n"), None::<(Vec<u32>, Vec<u32>)>);
+        assert_eq!(columns("12   34\n12\n"), None::<(Vec<u32>, Vec<u32>)>);
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        let input = "4294967295   4294967296\n";
+        assert_eq!(columns::<u32>(input), None);
+        assert_eq!(columns::<u64>(input), Some((vec![4294967295], vec![4294967296])));
+        assert_eq!(columns::<u64>("99999999999999999999   1\n"), None);
+    }
+
+    #[test]
+    fn test_uints() {
+        assert_eq!(uints::<u8>("6,1\n11,0\n").collect::<Vec<_>>(), [6, 1, 11, 0]);
+        assert_eq!(uints::<u64>("p=-12,34 v=5\n").collect::<Vec<_>>(), [12, 34, 5]);
+        assert_eq!(uints::<u64>("").next(), None);
+        assert_eq!(uint::<u8>(b"256"), None);
+        assert_eq!(uint::<u8>(b""), None);
+    }
+}
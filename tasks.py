@@ -19,9 +19,8 @@ import typing as t
 import webbrowser
 from datetime import datetime
 from functools import partial, wraps
-from os import chdir, environ, startfile
+from os import chdir, environ
 from pathlib import Path
-from uuid import uuid4
 
 import browser_cookie3
 import html2text
@@ -48,10 +47,20 @@ use std::fmt::Display;
 
 #[inline]
 pub fn solve() -> (impl Display, impl Display) {
+    solve_input(include_str!(concat!(env!("OUT_DIR"), "/input.txt")))
+}
+
+pub fn solve_input(_input: &str) -> (impl Display, impl Display) {
     ("TODO", "TODO")
 }\
 """
 
+BUILD = """\
+fn main() {
+    aoc_inputs::build();
+}\
+"""
+
 DEFAULT_BASELINE = "previous"
 
 WORKSPACE_MANIFEST_PATH = Path(__file__).parent / "Cargo.toml"
@@ -106,17 +115,50 @@ def in_root_dir(f):
     return inner
 
 
+def aoc_inputs(*args: str) -> None:
+    run(("cargo", "run", "--quiet", "--release", "--package", "aoc-inputs", "--", *args))
+
+
 @wrap_errors((requests.HTTPError,))
+@in_root_dir
 def refetch_inputs() -> None:
-    "Fetch the inputs that aren't present locally, since they cannot be stored in the repo."
+    "Fetch the inputs that aren't present locally, either in plaintext or encrypted."
+    fetched = []
     for day in Path(__file__).parent.glob("day*"):
         input_path = day / "src" / "input.txt"
-        if input_path.exists():
+        if input_path.exists() or input_path.with_suffix(".txt.enc").exists():
             continue
         day_num = int(day.name.removeprefix("day"))
         resp = session.get(f"https://adventofcode.com/{YEAR}/day/{day_num}/input")
         resp.raise_for_status()
         input_path.write_text(resp.text, newline="\n")
+        fetched.append(day.name)
+    if fetched:
+        aoc_inputs("encrypt", *fetched)
+
+
+@in_root_dir
+def keygen() -> None:
+    "Generate a new key for the encrypted inputs."
+    aoc_inputs("keygen")
+
+
+@in_root_dir
+def encrypt_inputs(*days: str) -> None:
+    "Encrypt the plaintext inputs, so that they can be committed."
+    aoc_inputs("encrypt", *days)
+
+
+@in_root_dir
+def decrypt_inputs(*days: str) -> None:
+    "Decrypt the encrypted inputs into plaintext, e.g. for prototyping."
+    aoc_inputs("decrypt", *days)
+
+
+@in_root_dir
+def rekey_inputs() -> None:
+    "Re-encrypt all inputs under a freshly generated key."
+    aoc_inputs("rekey")
 
 
 @arg("-d", "--day", choices=DAYS_LEFT, default=min(DAYS_LEFT, default=0), required=False)
@@ -157,15 +199,19 @@ def start_solve(day: int = min(DAYS_LEFT, default=0)) -> None:
             crate,
         )
     )
+    run(("cargo", "add", "--manifest-path", f"{crate}/Cargo.toml", "--build", "--path", "inputs", "aoc-inputs"))
 
     src = crate_path / "src"
     (src / "main.rs").write_text(MAIN.format(crate=crate), newline="\n")
     (src / "lib.rs").write_text(LIB, newline="\n")
     (src / "input.txt").write_text(puzzle_input, newline="\n")
+    (crate_path / "build.rs").write_text(BUILD, newline="\n")
+    aoc_inputs("encrypt", crate)
 
     benches = Path("benchmark", "benches")
     add_line(benches / "criterion.rs", f"    {crate},")
     add_line(benches / "iai.rs", f"    {crate}: {crate}_solve,")
+    add_line(Path("benchmark", "src", "lib.rs"), f"    {crate},")
 
     fetch_problem(YEAR, day)
 
@@ -233,6 +279,14 @@ def iai() -> None:
     run(("cargo", "bench", "--bench", "iai"))
 
 
+@in_root_dir
+def scaling(*days: str) -> None:
+    "Run the scaling benchmark on generated inputs, for the given days or all of them, and print growth exponents."
+    # Criterion takes a single filter, which is a regex.
+    filter = [f"scaling/({'|'.join(days)})"] if days else []
+    run(("cargo", "bench", "--bench", "scaling", "--", *filter))
+
+
 @aliases("wr")
 def watch_run() -> None:
     "Run the solution everytime it changes."
@@ -254,6 +308,21 @@ def run_release() -> None:
     run(("cargo", "run", "--release"))
 
 
+@aliases("ans")
+@in_root_dir
+def answers(*days: str, no_cache: bool = False, time: bool = False) -> None:
+    "Print the answers for the given days (or all of them), using the answer cache unless told otherwise."
+    flags = [flag for flag, on in (("--no-cache", no_cache), ("--time", time)) if on]
+    run(("cargo", "run", "--quiet", "--release", "--bin", "aoc", "--", *flags, *days))
+
+
+@in_root_dir
+def trace(*days: str, chrome: t.Optional[str] = None) -> None:
+    "Print a per-phase timing tree for the given days, optionally writing a Chrome trace too."
+    flags = ["--chrome-trace", chrome] if chrome else []
+    run(("cargo", "run", "--quiet", "--release", "--bin", "aoc", "--features", "trace", "--", "--trace", *flags, *days))
+
+
 @aliases("rp")
 def run_prototype() -> None:
     "Run a python file named prototype.py everytime something changes."
@@ -344,62 +413,15 @@ def set_completion_time() -> None:
         toml.dump(manifest, manifest_f)
 
 
+@arg("--part", type=int, choices=(1, 2))
 @in_root_dir
-def flamegraph(day: str, *, remote="linode") -> None:
-    "Run a flamegraph benchmark on a remote."
-    import tarfile
-    import tempfile
-
-    from rich.console import Console
-
-    console = Console()
-
-    def filter(info):
-        if any(s in info.name for s in (".git", "target")):
-            console.log(f"{info.name!r} [red]skipped.[/red]")
-            return None
-        console.log(f"{info.name!r} [green]added to tarball.[/green]")
-        return info
-
-    # Generate a zipped tarball of the current source code.
-    archive_stem = str(uuid4())
-    archive_name = f"{archive_stem}.tar.gz"
-    with tempfile.TemporaryDirectory() as tmpdir:
-        archive_path = Path(tmpdir, archive_name)
-        with console.status("Compressing..."), tarfile.open(archive_path, "w:gz") as tar:
-            tar.add(".", filter=filter)
-
-        # Upload it to the remote via scp and untar it.
-        run(("scp", "-C", archive_path, f"{remote}:/tmp/{archive_name}"))
-        run(("ssh", remote, "tar", "-xzf", f"/tmp/{archive_name}", "--one-top-level", "-C", "/tmp"))
-
-    # Run the benchmark on the remote.
-    run(
-        (
-            "ssh",
-            remote,
-            "cd",
-            f"/tmp/{archive_stem}",
-            "&&",
-            "CARGO_PROFILE_BENCH_DEBUG=true",
-            "cargo",
-            "flamegraph",
-            "--bench",
-            "criterion",
-            "--",
-            "--bench",
-            day,
-        )
-    )
-
-    # Download the flamegraph.
-    run(("scp", f"{remote}:/tmp/{archive_stem}/flamegraph.svg", "."))
-
-    # Remove the archive from the remote.
-    run(("ssh", remote, "rm", "-rf", f"/tmp/{archive_stem}", f"/tmp/{archive_name}"))
-
-    # Open the flamegraph.
-    startfile("flamegraph.svg")
+def flamegraph(day: str, part: t.Optional[int] = None, seconds: float = 5) -> None:
+    "Profile a day's solver locally and open the resulting flamegraph."
+    flags = [*(("--part", str(part)) if part else ()), "--seconds", str(seconds)]
+    environ["CARGO_PROFILE_RELEASE_DEBUG"] = "true"
+    run(("cargo", "run", "--quiet", "--release", "--bin", "aoc", "--features", "profile", "--", "profile", day, *flags))
+    stem = f"{day}-part{part}" if part else day
+    webbrowser.open(Path("target", "profile", f"{stem}.svg").resolve().as_uri())
 
 
 def main() -> None:
@@ -414,15 +436,22 @@ def main() -> None:
             compare_by_stashing,
             criterion,
             iai,
+            scaling,
             watch_run,
             do_run,
             run_release,
+            answers,
+            trace,
             run_prototype,
             show_session_cookie,
             measure_completion_time,
             set_completion_time,
             flamegraph,
             refetch_inputs,
+            keygen,
+            encrypt_inputs,
+            decrypt_inputs,
+            rekey_inputs,
         ),
     )
 
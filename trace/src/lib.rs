@@ -0,0 +1,28 @@
+//! Per-phase tracing spans for the solvers, compiled out unless the `enabled` feature is on.
+//!
+//! `span!("name")` times the rest of the enclosing block, while `span!("name", expr)` times just `expr` and evaluates
+//! to its value.
+#[cfg(feature = "enabled")]
+#[doc(hidden)]
+pub use tracing;
+
+#[cfg(feature = "enabled")]
+#[macro_export]
+macro_rules! span {
+    ($name:literal) => {
+        let _span = $crate::tracing::info_span!($name).entered();
+    };
+    ($name:literal, $body:expr) => {{
+        let _span = $crate::tracing::info_span!($name).entered();
+        $body
+    }};
+}
+
+#[cfg(not(feature = "enabled"))]
+#[macro_export]
+macro_rules! span {
+    ($name:literal) => {};
+    ($name:literal, $body:expr) => {
+        $body
+    };
+}